@@ -0,0 +1,529 @@
+use syn::{
+    meta::ParseNestedMeta, parenthesized, parse::ParseStream, punctuated::Punctuated, token,
+    Attribute, Error, Expr, Ident, LitBool, LitStr, Meta, Path, Result, Token, Type, Visibility,
+};
+
+/// Options given in `#[builder(...)]` attributes on the struct itself.
+#[derive(Default)]
+pub struct StructAttrs {
+    pub typestate: bool,
+    pub pattern: Pattern,
+    /// Visibility of every setter, private unless given.
+    pub vis: Option<Visibility>,
+    pub setter: SetterAttrs,
+    pub build_fn: BuildFnAttrs,
+    /// The generated code only uses `core`, and the error type does not
+    /// allocate.
+    pub no_std: bool,
+    /// Traits derived for the builder struct.
+    pub derives: Vec<Path>,
+    /// Attributes put on the builder struct as they are.
+    pub struct_attrs: Vec<Meta>,
+    /// Prefix of the environment variables read by `from_env`, which is only
+    /// generated when given.
+    pub env_prefix: Option<String>,
+    /// Separator of the items of a collection in an environment variable,
+    /// `,` unless given.
+    pub env_separator: Option<String>,
+    /// The builder derives `serde::Deserialize`, which needs the `serde`
+    /// feature of this crate.
+    pub serde: bool,
+}
+
+/// Options given in `build_fn(...)` on the struct.
+#[derive(Default)]
+pub struct BuildFnAttrs {
+    /// Function called with a reference to the builder before `build`
+    /// constructs the struct, returning `Result<(), String>`, or
+    /// `Result<(), &'static str>` with `no_std`.
+    pub validate: Option<Path>,
+}
+
+/// How setters and `build` take the builder, chosen with
+/// `#[builder(pattern = "...")]`.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Pattern {
+    /// Setters take `&mut self` and return `&mut Self`.
+    #[default]
+    Mutable,
+    /// Setters take `self` and return `Self`, `build` consumes the builder.
+    Owned,
+    /// Setters take `&self` and return an updated copy of the builder.
+    Immutable,
+}
+
+/// Options given in `#[builder(...)]` attributes on a field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub each: Option<Ident>,
+    pub default: Option<FieldDefault>,
+    pub setter: SetterAttrs,
+    /// Replaces the field's name in the builder, or the positional `_0`, `_1`,
+    /// ... of a tuple field.
+    pub name: Option<Ident>,
+    /// The field has no setter and is initialized by `build` from its
+    /// `default`.
+    pub skip: bool,
+    /// Visibility of the field's setters, overriding the struct's.
+    pub vis: Option<Visibility>,
+    /// The field's type derives Builder too, and is set through its own
+    /// builder instead of a setter.
+    pub sub_builder: bool,
+    /// How `merge` combines a collection with the one of the other builder.
+    pub merge: Option<Merge>,
+    /// Separator of the collection's items in its environment variable,
+    /// overriding the struct's.
+    pub env_separator: Option<String>,
+    /// `env = false`: `from_env` leaves the field alone, so its type need not
+    /// implement FromStr.
+    pub skip_env: bool,
+    /// The field's type is not looked into, so a type named `Option` or `Vec`
+    /// that is not the standard one is set and stored like any other type.
+    pub opaque: bool,
+}
+
+/// Chosen with `#[builder(merge = "...")]` on a collection.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Merge {
+    /// The other builder's items replace these, unless there are none.
+    Replace,
+    /// The other builder's items are added after these.
+    Append,
+}
+
+/// Options given in `setter(...)`, either on a field or on the struct where
+/// they apply to every field.
+#[derive(Default)]
+pub struct SetterAttrs {
+    /// Setters accept any `impl Into<T>`.
+    pub into: bool,
+    /// A `try_` setter accepting any `TryInto<T>` is added next to the setter
+    /// of the whole field.
+    pub try_into: bool,
+    /// Prepended to the name of the setter of the whole field.
+    pub prefix: Option<String>,
+    /// Whether the setter of an Option field takes the value inside the
+    /// Option, which is the default, or the whole Option.
+    pub strip_option: Option<bool>,
+    /// Function turning the arguments of the setter of the whole field into
+    /// the value it stores. Only given on a field.
+    pub with: Option<SetterWith>,
+}
+
+/// `setter(with = "path::to::fn(arg: Type, ...)")`. A derive macro only sees
+/// the path of the function, not its signature, so the arguments the setter
+/// takes and passes on to the function are spelled out after it.
+pub struct SetterWith {
+    pub path: Path,
+    pub args: Vec<(Ident, Type)>,
+}
+
+impl SetterAttrs {
+    /// Applies the struct-level options on top of the field's own.
+    pub fn inherit(&mut self, parent: &SetterAttrs) {
+        self.into |= parent.into;
+        self.try_into |= parent.try_into;
+        if self.prefix.is_none() {
+            self.prefix.clone_from(&parent.prefix);
+        }
+        if self.strip_option.is_none() {
+            self.strip_option = parent.strip_option;
+        }
+    }
+}
+
+/// Value used for a field whose setter was never called.
+pub enum FieldDefault {
+    /// `#[builder(default)]`, uses `Default::default()`.
+    Trait,
+    /// `#[builder(default = "expr")]`.
+    Expr(Expr),
+}
+
+pub fn parse_struct_attrs(attrs: &[Attribute]) -> Result<StructAttrs> {
+    let mut struct_attrs = StructAttrs::default();
+    let mut pattern_lit = None;
+    let mut env_prefix_lit = None;
+    let mut serde_path = None;
+    let mut keys = Keys::new(&[
+        "typestate",
+        "pattern",
+        "vis",
+        "setter",
+        "build_fn",
+        "no_std",
+        "derive",
+        "struct_attr",
+        "env_prefix",
+        "env_separator",
+        "serde",
+    ]);
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            match keys.check(&meta)? {
+                "typestate" => struct_attrs.typestate = true,
+                "pattern" => {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    struct_attrs.pattern = match lit.value().as_str() {
+                        "mutable" => Pattern::Mutable,
+                        "owned" => Pattern::Owned,
+                        "immutable" => Pattern::Immutable,
+                        _ => {
+                            return Err(Error::new(
+                                lit.span(),
+                                "expected `mutable`, `owned` or `immutable`",
+                            ))
+                        }
+                    };
+                    pattern_lit = Some(lit);
+                }
+                "vis" => struct_attrs.vis = Some(parse_vis(&meta)?),
+                "setter" => parse_setter_attrs(&meta, &mut struct_attrs.setter, false)?,
+                "build_fn" => parse_build_fn_attrs(&meta, &mut struct_attrs.build_fn)?,
+                "no_std" => struct_attrs.no_std = true,
+                "derive" => meta.parse_nested_meta(|meta| {
+                    struct_attrs.derives.push(meta.path);
+                    Ok(())
+                })?,
+                "struct_attr" => {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let attrs = Punctuated::<Meta, Token![,]>::parse_terminated(&content)?;
+                    struct_attrs.struct_attrs.extend(attrs);
+                }
+                "env_prefix" => {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    struct_attrs.env_prefix = Some(lit.value());
+                    env_prefix_lit = Some(lit);
+                }
+                "env_separator" => {
+                    let separator: LitStr = meta.value()?.parse()?;
+                    struct_attrs.env_separator = Some(parse_env_separator(&separator)?);
+                }
+                "serde" => {
+                    if !cfg!(feature = "serde") {
+                        return Err(
+                            meta.error("`serde` requires the `serde` feature of derive_builder")
+                        );
+                    }
+                    struct_attrs.serde = true;
+                    serde_path = Some(meta.path.clone());
+                }
+                _ => unreachable!(),
+            }
+            Ok(())
+        })?;
+    }
+    if let Some(lit) = pattern_lit {
+        if struct_attrs.typestate && struct_attrs.pattern != Pattern::Owned {
+            return Err(Error::new(
+                lit.span(),
+                "a typestate builder always uses the owned pattern",
+            ));
+        }
+    }
+    if let Some(path) = serde_path {
+        if struct_attrs.typestate {
+            return Err(Error::new_spanned(
+                path,
+                "a typestate builder cannot derive Deserialize",
+            ));
+        }
+    }
+    if let Some(lit) = env_prefix_lit {
+        if struct_attrs.typestate {
+            return Err(Error::new(
+                lit.span(),
+                "a typestate builder cannot be filled from the environment",
+            ));
+        }
+        if struct_attrs.no_std {
+            return Err(Error::new(
+                lit.span(),
+                "`env_prefix` is not available with `no_std`",
+            ));
+        }
+    }
+    Ok(struct_attrs)
+}
+
+pub fn parse_field_attrs(attrs: &[Attribute]) -> Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+    let mut keys = Keys::new(&[
+        "each",
+        "default",
+        "setter",
+        "name",
+        "skip",
+        "vis",
+        "sub_builder",
+        "merge",
+        "env_separator",
+        "env",
+        "opaque",
+    ]);
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            match keys.check(&meta)? {
+                "each" => {
+                    let name: LitStr = meta.value()?.parse()?;
+                    field_attrs.each = Some(name.parse()?);
+                }
+                "default" => {
+                    if meta.input.peek(Token![=]) {
+                        let expr: LitStr = meta.value()?.parse()?;
+                        field_attrs.default = Some(FieldDefault::Expr(expr.parse()?));
+                    } else {
+                        field_attrs.default = Some(FieldDefault::Trait);
+                    }
+                }
+                "setter" => parse_setter_attrs(&meta, &mut field_attrs.setter, true)?,
+                "name" => {
+                    let name: LitStr = meta.value()?.parse()?;
+                    field_attrs.name = Some(name.parse()?);
+                }
+                "skip" => field_attrs.skip = true,
+                "vis" => field_attrs.vis = Some(parse_vis(&meta)?),
+                "sub_builder" => field_attrs.sub_builder = true,
+                "merge" => {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    field_attrs.merge = match lit.value().as_str() {
+                        "replace" => Some(Merge::Replace),
+                        "append" => Some(Merge::Append),
+                        _ => return Err(Error::new(lit.span(), "expected `replace` or `append`")),
+                    };
+                }
+                "env_separator" => {
+                    let separator: LitStr = meta.value()?.parse()?;
+                    field_attrs.env_separator = Some(parse_env_separator(&separator)?);
+                }
+                "env" => {
+                    let env: LitBool = meta.value()?.parse()?;
+                    field_attrs.skip_env = !env.value;
+                }
+                "opaque" => field_attrs.opaque = true,
+                _ => unreachable!(),
+            }
+            Ok(())
+        })?;
+    }
+    Ok(field_attrs)
+}
+
+/// Parses `setter(...)`, where `with` is only accepted on a field.
+fn parse_setter_attrs(
+    meta: &ParseNestedMeta,
+    setter: &mut SetterAttrs,
+    on_field: bool,
+) -> Result<()> {
+    let mut keys = Keys::new(if on_field {
+        &["into", "try_into", "prefix", "strip_option", "with"]
+    } else {
+        &["into", "try_into", "prefix", "strip_option"]
+    });
+    meta.parse_nested_meta(|meta| {
+        match keys.check(&meta)? {
+            "into" => setter.into = true,
+            "try_into" => setter.try_into = true,
+            "prefix" => {
+                let prefix: LitStr = meta.value()?.parse()?;
+                // The prefix must make an identifier out of any field name.
+                if syn::parse_str::<Ident>(&format!("{}x", prefix.value())).is_err() {
+                    return Err(Error::new(
+                        prefix.span(),
+                        "the prefix must be the start of an identifier",
+                    ));
+                }
+                setter.prefix = Some(prefix.value());
+            }
+            "strip_option" => {
+                if meta.input.peek(Token![=]) {
+                    let strip_option: LitBool = meta.value()?.parse()?;
+                    setter.strip_option = Some(strip_option.value);
+                } else {
+                    setter.strip_option = Some(true);
+                }
+            }
+            "with" => {
+                let with: LitStr = meta.value()?.parse()?;
+                setter.with = Some(with.parse_with(parse_setter_with)?);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    })
+}
+
+fn parse_build_fn_attrs(meta: &ParseNestedMeta, build_fn: &mut BuildFnAttrs) -> Result<()> {
+    let mut keys = Keys::new(&["validate"]);
+    meta.parse_nested_meta(|meta| {
+        match keys.check(&meta)? {
+            "validate" => {
+                let path: LitStr = meta.value()?.parse()?;
+                build_fn.validate = Some(path.parse()?);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    })
+}
+
+fn parse_env_separator(separator: &LitStr) -> Result<String> {
+    let value = separator.value();
+    if value.is_empty() {
+        return Err(Error::new(
+            separator.span(),
+            "the separator cannot be empty",
+        ));
+    }
+    Ok(value)
+}
+
+fn parse_setter_with(input: ParseStream) -> Result<SetterWith> {
+    let path: Path = input.parse()?;
+    if !input.peek(token::Paren) {
+        return Err(input.error(
+            "expected the setter's arguments after the function, as in `path::to::fn(arg: Type)`",
+        ));
+    }
+    let content;
+    parenthesized!(content in input);
+    let mut args = Vec::new();
+    while !content.is_empty() {
+        let name: Ident = content.parse()?;
+        content.parse::<Token![:]>()?;
+        let ty: Type = content.parse()?;
+        args.push((name, ty));
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<Token![,]>()?;
+    }
+    Ok(SetterWith { path, args })
+}
+
+/// The keys accepted in one `#[builder(...)]` list, or in a nested list like
+/// `setter(...)`, and the ones already given.
+struct Keys {
+    valid: &'static [&'static str],
+    seen: Vec<&'static str>,
+}
+
+impl Keys {
+    fn new(valid: &'static [&'static str]) -> Self {
+        Keys {
+            valid,
+            seen: Vec::new(),
+        }
+    }
+
+    /// The key of `meta`, which must be one of the valid keys and must not
+    /// have been given before. An unknown key is reported with the closest
+    /// valid one as a suggestion.
+    fn check(&mut self, meta: &ParseNestedMeta) -> Result<&'static str> {
+        let name = meta
+            .path
+            .get_ident()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let Some(&key) = self.valid.iter().find(|key| **key == name) else {
+            return Err(meta.error(self.unknown_key_message(&name)));
+        };
+        if self.seen.contains(&key) {
+            return Err(meta.error(format!("duplicate attribute `{key}`")));
+        }
+        self.seen.push(key);
+        Ok(key)
+    }
+
+    fn unknown_key_message(&self, name: &str) -> String {
+        let closest = self
+            .valid
+            .iter()
+            .map(|key| (edit_distance(name, key), key))
+            .filter(|(distance, key)| *distance <= 2 && *distance < key.len())
+            .min_by_key(|(distance, _)| *distance);
+        if let Some((_, key)) = closest {
+            return format!("unknown attribute `{name}`, did you mean `{key}`?");
+        }
+        let mut expected = String::new();
+        for (i, key) in self.valid.iter().enumerate() {
+            if i > 0 {
+                expected += if i + 1 == self.valid.len() {
+                    " or "
+                } else {
+                    ", "
+                };
+            }
+            expected += &format!("`{key}`");
+        }
+        if name.is_empty() {
+            format!("expected {expected}")
+        } else {
+            format!("unknown attribute `{name}`, expected {expected}")
+        }
+    }
+}
+
+/// Number of single character insertions, deletions, substitutions and swaps
+/// of adjacent characters turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // `distances[i][j]` is the distance between the first `i` characters of
+    // `a` and the first `j` characters of `b`.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Parses `vis = "..."`, where an empty string keeps the setters private.
+fn parse_vis(meta: &ParseNestedMeta) -> Result<Visibility> {
+    let vis: LitStr = meta.value()?.parse()?;
+    vis.parse()
+}
+
+/// The options among `keys` in the `#[serde(...)]` attributes of the item or
+/// field, which are repeated on the builder so that it deserializes from the
+/// same input.
+pub fn parse_serde_attrs(attrs: &[Attribute], keys: &[&str]) -> Result<Vec<Meta>> {
+    let mut serde_attrs = vec![];
+    if !cfg!(feature = "serde") {
+        return Ok(serde_attrs);
+    }
+    for attr in attrs {
+        if !attr.path().is_ident("serde") {
+            continue;
+        }
+        let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        serde_attrs.extend(
+            metas
+                .into_iter()
+                .filter(|meta| keys.iter().any(|key| meta.path().is_ident(key))),
+        );
+    }
+    Ok(serde_attrs)
+}
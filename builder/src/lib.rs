@@ -1,11 +1,18 @@
+use attrs::{
+    parse_field_attrs, parse_serde_attrs, parse_struct_attrs, FieldAttrs, FieldDefault, Merge,
+    Pattern, SetterWith, StructAttrs,
+};
 use proc_macro::TokenStream;
-use proc_macro2::{Ident, Literal};
+use proc_macro2::Ident;
 use quote::{format_ident, quote};
 use syn::{
-    parse::Parse, parse2, parse_macro_input, Attribute, Data, DataStruct, DeriveInput,
-    GenericArgument, Meta, PathArguments, Token, Type,
+    ext::IdentExt, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields,
+    GenericArgument, GenericParam, Generics, Member, Meta, Path, PathArguments, PathSegment, Type,
+    Visibility,
 };
 
+mod attrs;
+
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -18,44 +25,240 @@ pub fn derive(input: TokenStream) -> TokenStream {
     TokenStream::from(code)
 }
 
+/// The struct or enum deriving Builder.
+struct Item<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    attrs: StructAttrs,
+    /// An enum has one builder per variant, none of which can be created from
+    /// an existing value of the enum.
+    is_enum: bool,
+    /// Container options of the item's own `#[serde(...)]` attributes.
+    serde: Vec<Meta>,
+}
+
+/// One generated builder: the only one of a struct, or one per enum variant.
+struct Builder<'a> {
+    ident: Ident,
+    error_ident: Ident,
+    /// Method on the item which returns an empty builder.
+    builder_fn: Ident,
+    /// Path naming the struct or variant in the expression built by `build`.
+    constructor: proc_macro2::TokenStream,
+    fields: Vec<Field<'a>>,
+    /// Fields marked `skip`, which have no setter and are computed by `build`.
+    skipped_fields: Vec<Field<'a>>,
+    /// `#[serde(...)]` options put on the builder with the `serde` feature.
+    serde: Vec<Meta>,
+}
+
+struct Field<'a> {
+    /// Name of the field's storage in the builder and of its setter.
+    ident: Ident,
+    /// The field of the built item, a name or a tuple index.
+    member: Member,
+    ty: &'a Type,
+    attrs: FieldAttrs,
+    /// The field's doc comments, repeated on its setters.
+    docs: Vec<&'a Attribute>,
+    /// Builder of a `sub_builder` field, found by naming convention from the
+    /// field's type.
+    sub_builder: Option<SubBuilder>,
+    /// The field's `rename`, `alias` and `skip` serde options.
+    serde: Vec<Meta>,
+}
+
+/// `ServerBuilder<T>` and `ServerBuilderError` for a field of type `Server<T>`.
+struct SubBuilder {
+    ty: Type,
+    error: Path,
+}
+
+impl Field<'_> {
+    /// Whether `build` fails when the field's setter was never called.
+    fn is_required(&self) -> bool {
+        !self.is_option()
+            && !self.is_collection()
+            && self.attrs.default.is_none()
+            && self.sub_builder.is_none()
+    }
+
+    /// Whether the field is stored as a collection which starts out empty and
+    /// is filled in by its setters: any Vec, and any field with `each`.
+    fn is_collection(&self) -> bool {
+        (is_vec_type(self.ty) && !self.attrs.opaque) || self.attrs.each.is_some()
+    }
+
+    /// The type inside the field's Option, unless the field is `opaque`.
+    fn option_type(&self) -> Option<&Type> {
+        if self.attrs.opaque {
+            None
+        } else {
+            get_option_underlying_type(self.ty)
+        }
+    }
+
+    fn is_option(&self) -> bool {
+        self.option_type().is_some()
+    }
+
+    /// Name of the setter of the whole field, with the `setter(prefix)` if any.
+    fn setter_name(&self) -> Ident {
+        match &self.attrs.setter.prefix {
+            Some(prefix) => format_ident!("{}{}", prefix, self.ident),
+            None => self.ident.clone(),
+        }
+    }
+}
+
+/// A builder method that updates one field, independent of how the builder is
+/// passed to it.
+struct Setter {
+    /// Doc comments of the method.
+    docs: proc_macro2::TokenStream,
+    name: Ident,
+    /// Generic parameters of the method, with their angle brackets.
+    generics: proc_macro2::TokenStream,
+    args: proc_macro2::TokenStream,
+    /// Statements updating the builder, which is in scope as `__builder`.
+    body: proc_macro2::TokenStream,
+    /// Error type of a fallible setter, whose body may return early with `?`.
+    error: Option<proc_macro2::TokenStream>,
+}
+
 fn generate_code(input: DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
-    let item_ident = input.ident;
-    let builder_ident = format_ident!("{item_ident}Builder");
+    let item = Item {
+        ident: &input.ident,
+        generics: &input.generics,
+        attrs: parse_struct_attrs(&input.attrs)?,
+        is_enum: matches!(input.data, Data::Enum(_)),
+        serde: parse_serde_attrs(
+            &input.attrs,
+            &["rename_all", "rename_all_fields", "deny_unknown_fields"],
+        )?,
+    };
 
-    let data_struct = get_data_struct(&input.data, &item_ident)?;
-    let struct_impl = generate_struct_impl(&item_ident, &builder_ident, data_struct);
-    let builder = generate_builder_struct(&item_ident, &builder_ident, data_struct)?;
+    let mut code = proc_macro2::TokenStream::new();
+    for builder in get_builders(&input.data, &item)? {
+        code.extend(generate_builder(&item, &builder)?);
+    }
+    Ok(code)
+}
+
+fn generate_builder(
+    item: &Item,
+    builder: &Builder,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut error = generate_error_enum(item, builder);
+    error.extend(generate_env_error(item, builder));
+    if item.attrs.typestate {
+        let builder = generate_typestate_builder(item, builder)?;
+        return Ok(quote! {
+            #builder
+            #error
+        });
+    }
+
+    let struct_impl = generate_struct_impl(item, builder);
+    let from_item = if item.is_enum {
+        quote!()
+    } else {
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let builder_ident = &builder.ident;
+        generate_from_item(item, builder, quote!(#builder_ident #ty_generics), quote!())
+    };
+    let builder = generate_builder_struct(item, builder)?;
 
     Ok(quote! {
         #struct_impl
+        #from_item
         #builder
+        #error
     })
 }
 
-fn generate_struct_impl(
-    item_ident: &Ident,
-    builder_ident: &Ident,
-    data_struct: &DataStruct,
-) -> proc_macro2::TokenStream {
-    let field_inits = data_struct.fields.iter().map(|field| {
-        let name = field.ident.as_ref().unwrap();
-        let ty = &field.ty;
-        if is_vec_type(ty) {
-            quote! {
-                #name: vec![],
+fn generate_struct_impl(item: &Item, builder: &Builder) -> proc_macro2::TokenStream {
+    let item_ident = item.ident;
+    let builder_ident = &builder.ident;
+    let builder_fn = &builder.builder_fn;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let field_inits = generate_field_inits(&builder.fields);
+    let constness = generate_builder_fn_constness(&builder.fields);
+
+    // A builder which is not owned already requires its fields to be Clone, so
+    // that it can copy them out of the builder in `build`. A nested builder is
+    // built in place instead, so its struct is turned into a builder by its
+    // own `to_builder` rather than cloned.
+    let to_builder = if item.is_enum || item.attrs.pattern == Pattern::Owned {
+        quote!()
+    } else {
+        let prefilled_fields = builder.fields.iter().map(|field| {
+            let name = &field.ident;
+            let member = &field.member;
+            let value = if field.sub_builder.is_some() {
+                quote!(core::option::Option::Some(self.#member.to_builder()))
+            } else {
+                generate_stored_value(field, quote!(core::clone::Clone::clone(&self.#member)))
+            };
+            quote!(#name: #value,)
+        });
+        quote! {
+            pub fn to_builder(&self) -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #(#prefilled_fields)*
+                    __phantom: core::marker::PhantomData,
+                }
             }
-        } else {
-            quote! {
-                #name: None,
+        }
+    };
+
+    quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            pub #constness fn #builder_fn() -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #(#field_inits)*
+                    __phantom: core::marker::PhantomData,
+                }
             }
+
+            #to_builder
         }
+    }
+}
+
+/// `From` impl turning an existing struct into a builder with every field set,
+/// which is `builder_ty`. Skipped fields are dropped and computed again by
+/// `build`.
+fn generate_from_item(
+    item: &Item,
+    builder: &Builder,
+    builder_ty: proc_macro2::TokenStream,
+    extra_inits: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let item_ident = item.ident;
+    let builder_ident = &builder.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let bindings = builder.fields.iter().map(|field| {
+        let name = &field.ident;
+        match &field.member {
+            Member::Named(member) if member == name => quote!(#name,),
+            member => quote!(#member: #name,),
+        }
+    });
+    let prefilled_fields = builder.fields.iter().map(|field| {
+        let name = &field.ident;
+        let value = generate_stored_value(field, quote!(#name));
+        quote!(#name: #value,)
     });
 
     quote! {
-        impl #item_ident {
-            pub fn builder() -> #builder_ident {
+        impl #impl_generics core::convert::From<#item_ident #ty_generics> for #builder_ty #where_clause {
+            fn from(__item: #item_ident #ty_generics) -> Self {
+                let #item_ident { #(#bindings)* .. } = __item;
                 #builder_ident {
-                    #(#field_inits)*
+                    #(#prefilled_fields)*
+                    __phantom: core::marker::PhantomData,
+                    #extra_inits
                 }
             }
         }
@@ -63,128 +266,1239 @@ fn generate_struct_impl(
 }
 
 fn generate_builder_struct(
-    item_ident: &Ident,
-    builder_ident: &Ident,
-    data_struct: &DataStruct,
+    item: &Item,
+    builder: &Builder,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
-    let fields = data_struct.fields.iter().map(|field| {
-        let name = field.ident.as_ref().unwrap();
-        let ty = &field.ty;
-        if is_option_type(ty) || is_vec_type(ty) {
-            quote! {
-                #name: #ty,
-            }
-        } else {
-            quote! {
-                #name: std::option::Option<#ty>,
-            }
-        }
+    let item_ident = item.ident;
+    let generics = item.generics;
+    let pattern = item.attrs.pattern;
+    let Builder {
+        ident: builder_ident,
+        error_ident,
+        fields,
+        ..
+    } = builder;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let phantom_type = generate_phantom_type(generics);
+    let builder_fields = generate_builder_fields(fields);
+    let serde_attrs = generate_serde_attrs(item, builder);
+    let builder_attrs = generate_builder_attrs(&item.attrs);
+    let serde_field_attrs = generate_serde_field_attrs(item, fields);
+    let serde_skip = if item.attrs.serde {
+        quote!(#[serde(skip)])
+    } else {
+        quote!()
+    };
+
+    let clone_builder = generate_clone_builder(builder_ident, fields);
+    let field_mutators = fields.iter().flat_map(|field| match &field.sub_builder {
+        Some(sub_builder) => vec![generate_sub_builder_method(field, sub_builder)],
+        None => generate_setters(field)
+            .into_iter()
+            .map(|setter| {
+                generate_setter_method(pattern, &clone_builder, field.attrs.vis.as_ref(), &setter)
+            })
+            .collect(),
     });
 
+    let merge_method = generate_merge_method(item, builder, &clone_builder);
+    let from_env_method = generate_from_env_method(item, builder, &clone_builder);
+    let missing_fields_check = generate_missing_fields_check(item, builder);
+
+    // An owned builder is consumed by `build`, so its fields can be moved out
+    // instead of cloned.
+    let construction = generate_construction(builder, pattern == Pattern::Owned);
+
+    let build_receiver = match pattern {
+        Pattern::Mutable => quote!(&mut self),
+        Pattern::Owned => quote!(self),
+        Pattern::Immutable => quote!(&self),
+    };
+    let validation = generate_validation(error_ident, &item.attrs);
+    let build_method = quote! {
+        pub fn build(#build_receiver) -> core::result::Result<#item_ident #ty_generics, #error_ident> {
+            #missing_fields_check
+            #validation
+            #construction
+        }
+    };
+
+    Ok(quote! {
+        #serde_attrs
+        #builder_attrs
+        pub struct #builder_ident #generics #where_clause {
+            #(#serde_field_attrs #builder_fields)*
+            #serde_skip
+            __phantom: #phantom_type,
+        }
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(#field_mutators)*
+            #merge_method
+            #from_env_method
+            #build_method
+        }
+    })
+}
+
+fn generate_typestate_builder(
+    item: &Item,
+    builder: &Builder,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let item_ident = item.ident;
+    let generics = item.generics;
+    let Builder {
+        ident: builder_ident,
+        error_ident,
+        builder_fn,
+        fields,
+        ..
+    } = builder;
+    let set_ident = format_ident!("{builder_ident}Set");
+    let unset_ident = format_ident!("{builder_ident}Unset");
+
+    // Every required field gets its own state parameter, in declaration order.
+    let required_fields: Vec<&Ident> = fields
+        .iter()
+        .filter(|field| field.is_required())
+        .map(|field| &field.ident)
+        .collect();
+    let states: Vec<Ident> = (0..required_fields.len())
+        .map(|i| format_ident!("__S{i}"))
+        .collect();
+
+    // The item's own generic parameters come first, followed by the states.
+    let item_args = generate_generic_args(generics);
+    let (item_impl_generics, item_ty_generics, where_clause) = generics.split_for_impl();
+    let phantom_type = generate_phantom_type(generics);
+
+    let mut decl_generics = generics.clone();
+    let mut impl_generics = generics.clone();
+    for state in &states {
+        decl_generics
+            .params
+            .push(parse_quote!(#state = #unset_ident));
+        impl_generics.params.push(parse_quote!(#state));
+    }
+    let (impl_generics, ty_generics, _) = impl_generics.split_for_impl();
+
+    let field_names: Vec<&Ident> = fields.iter().map(|field| &field.ident).collect();
+    let builder_fields = generate_builder_fields(fields);
+    let field_inits = generate_field_inits(fields);
+    let constness = generate_builder_fn_constness(fields);
+
     let mut field_mutators = vec![];
-    for field in &data_struct.fields {
-        let name = field.ident.as_ref().unwrap();
-        let ty = &field.ty;
-        let mutator = if is_option_type(ty) {
-            let underlying_type = get_option_underlying_type(ty);
-            quote! {
-                fn #name(&mut self, #name: #underlying_type) -> &mut Self {
-                    self.#name = Some(#name);
-                    self
-                }
-            }
-        } else if is_vec_type(ty) {
-            if let Some(singular_name) = get_singular_name(&field.attrs)? {
-                let underlying_type = get_vec_underlying_type(ty);
+    for field in fields {
+        let name = &field.ident;
+        let ty = field.ty;
+        let mutator = if !field.is_required() {
+            let setters = generate_setters(field).into_iter().map(|setter| {
+                generate_setter_method(Pattern::Owned, &quote!(), field.attrs.vis.as_ref(), &setter)
+            });
+            quote!(#(#setters)*)
+        } else {
+            // Setting a required field moves the builder into a type whose
+            // state parameter for that field is `Set`.
+            let next_states = required_fields
+                .iter()
+                .zip(&states)
+                .map(|(required, state)| {
+                    if *required == name {
+                        quote!(#set_ident)
+                    } else {
+                        quote!(#state)
+                    }
+                });
+            let (args, value) = generate_whole_setter_args(field, ty);
+            let move_fields = |value: &proc_macro2::TokenStream| {
+                let moved_fields = field_names.iter().map(|other| {
+                    if *other == name {
+                        quote!(#name: core::option::Option::Some(#value),)
+                    } else {
+                        quote!(#other: self.#other,)
+                    }
+                });
                 quote! {
-                    fn #singular_name(&mut self, #singular_name: #underlying_type) -> &mut Self {
-                        self.#name.push(#singular_name);
-                        self
+                    #builder_ident {
+                        #(#moved_fields)*
+                        __phantom: core::marker::PhantomData,
+                        __state: core::marker::PhantomData,
                     }
                 }
-            } else {
+            };
+            let setter_name = field.setter_name();
+            let docs = &field.docs;
+            let vis = &field.attrs.vis;
+            let next_builder = quote!(#builder_ident<#(#item_args,)* #(#next_states),*>);
+            let moved = move_fields(&value);
+            let try_setter = if field.attrs.setter.try_into {
+                let try_setter_name = format_ident!("try_{}", setter_name);
+                let moved = move_fields(&quote!(core::convert::TryInto::try_into(#name)?));
+                let try_docs = generate_try_setter_docs(field);
                 quote! {
-                    fn #name(&mut self, #name: #ty) -> &mut Self {
-                        self.#name = #name;
-                        self
+                    #try_docs
+                    #vis fn #try_setter_name<__V: core::convert::TryInto<#ty>>(
+                        self,
+                        #name: __V,
+                    ) -> core::result::Result<#next_builder, __V::Error> {
+                        core::result::Result::Ok(#moved)
                     }
                 }
-            }
-        } else {
+            } else {
+                quote!()
+            };
             quote! {
-                fn #name(&mut self, #name: #ty) -> &mut Self {
-                    self.#name = Some(#name);
-                    self
+                #(#docs)*
+                #vis fn #setter_name(self, #args) -> #next_builder {
+                    #moved
                 }
+
+                #try_setter
             }
         };
         field_mutators.push(mutator);
     }
 
-    let field_set_checks = data_struct.fields.iter().map(|field| {
-        let name = field.ident.as_ref().unwrap();
-        if is_option_type(&field.ty) || is_vec_type(&field.ty) {
-            quote!{}
+    let construction = generate_construction(builder, true);
+
+    // The state markers derive the same traits as the builder, so that the
+    // derived impls apply whatever the state. Only an unset builder can be
+    // created by Default.
+    let derives = &item.attrs.derives;
+    let builder_attrs = generate_builder_attrs(&item.attrs);
+    let set_derives: Vec<&Path> = derives
+        .iter()
+        .filter(|path| !is_default_path(path))
+        .collect();
+    let set_attrs = generate_derive(&set_derives);
+    let unset_attrs = generate_derive(derives);
+
+    let all_set: Vec<&Ident> = required_fields.iter().map(|_| &set_ident).collect();
+    let validation = generate_validation(error_ident, &item.attrs);
+    let from_item = if item.is_enum {
+        quote!()
+    } else {
+        generate_from_item(
+            item,
+            builder,
+            quote!(#builder_ident<#(#item_args,)* #(#all_set),*>),
+            quote!(__state: core::marker::PhantomData,),
+        )
+    };
+
+    Ok(quote! {
+        #set_attrs
+        pub struct #set_ident;
+        #unset_attrs
+        pub struct #unset_ident;
+
+        #builder_attrs
+        pub struct #builder_ident #decl_generics #where_clause {
+            #(#builder_fields)*
+            __phantom: #phantom_type,
+            __state: core::marker::PhantomData<(#(#states,)*)>,
+        }
+
+        impl #item_impl_generics #item_ident #item_ty_generics #where_clause {
+            pub #constness fn #builder_fn() -> #builder_ident #item_ty_generics {
+                #builder_ident {
+                    #(#field_inits)*
+                    __phantom: core::marker::PhantomData,
+                    __state: core::marker::PhantomData,
+                }
+            }
+        }
+
+        #from_item
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(#field_mutators)*
+        }
+
+        impl #item_impl_generics #builder_ident<#(#item_args,)* #(#all_set),*> #where_clause {
+            pub fn build(self) -> core::result::Result<#item_ident #item_ty_generics, #error_ident> {
+                #validation
+                #construction
+            }
+        }
+    })
+}
+
+/// Call to the user's `build_fn(validate = "...")` function, if any, which
+/// `build` makes before constructing the struct.
+fn generate_validation(
+    error_ident: &Ident,
+    struct_attrs: &StructAttrs,
+) -> proc_macro2::TokenStream {
+    match &struct_attrs.build_fn.validate {
+        Some(validate) => quote! {
+            #validate(&self).map_err(#error_ident::Validation)?;
+        },
+        None => quote!(),
+    }
+}
+
+fn generate_setters(field: &Field) -> Vec<Setter> {
+    let name = &field.ident;
+    let ty = field.ty;
+    let option_type = field.option_type();
+    // Type taken by the setter of the whole field, and whether the builder
+    // stores it wrapped in `Some`.
+    let (value_ty, wrap_in_some) = match option_type {
+        _ if field.is_collection() => (ty, false),
+        Some(_) if field.attrs.setter.strip_option == Some(false) => (ty, false),
+        Some(underlying_type) => (underlying_type, true),
+        None => (ty, true),
+    };
+    let store = |value: proc_macro2::TokenStream| {
+        if wrap_in_some {
+            quote!(__builder.#name = core::option::Option::Some(#value);)
         } else {
+            quote!(__builder.#name = #value;)
+        }
+    };
+
+    let (args, value) = generate_whole_setter_args(field, value_ty);
+    let docs = &field.docs;
+    let mut setters = vec![Setter {
+        docs: quote!(#(#docs)*),
+        name: field.setter_name(),
+        generics: quote!(),
+        args,
+        body: store(value),
+        error: None,
+    }];
+    if field.attrs.setter.try_into {
+        let body = store(quote!(core::convert::TryInto::try_into(#name)?));
+        setters.push(Setter {
+            docs: generate_try_setter_docs(field),
+            name: format_ident!("try_{}", field.setter_name()),
+            generics: quote!(<__V: core::convert::TryInto<#value_ty>>),
+            args: quote!(#name: __V),
+            body,
+            error: Some(quote!(__V::Error)),
+        });
+    }
+    if let Some(singular_name) = &field.attrs.each {
+        setters.push(generate_each_setter(field, singular_name));
+        setters.push(generate_extend_setter(field));
+    }
+    if option_type.is_some() && !field.is_collection() {
+        setters.push(generate_clear_setter(field));
+    }
+    setters
+}
+
+/// A one-line doc comment. The field's own doc comments only describe the
+/// setter of the whole field, so its other setters get one of these instead.
+fn generate_doc(doc: String) -> proc_macro2::TokenStream {
+    let doc = format!(" {doc}");
+    quote!(#[doc = #doc])
+}
+
+fn generate_try_setter_docs(field: &Field) -> proc_macro2::TokenStream {
+    generate_doc(format!(
+        "Sets `{}` to a value converted with `TryInto`, or returns the conversion's error.",
+        field.ident.unraw(),
+    ))
+}
+
+/// Setter resetting an Option field to `None`, named after the field with a
+/// `clear_` prefix.
+fn generate_clear_setter(field: &Field) -> Setter {
+    let name = &field.ident;
+    Setter {
+        docs: generate_doc(format!("Resets `{}` to `None`.", name.unraw())),
+        name: format_ident!("clear_{}", name),
+        generics: quote!(),
+        args: quote!(),
+        body: quote!(__builder.#name = core::option::Option::None;),
+        error: None,
+    }
+}
+
+/// Setter adding one item to a collection. Maps take the key and the value as
+/// separate arguments.
+fn generate_each_setter(field: &Field, singular_name: &Ident) -> Setter {
+    let name = &field.ident;
+    let ty = field.ty;
+    let (args, item) = match get_collection_item(ty) {
+        CollectionItem::Single(item_ty) => {
+            let (arg_ty, value) = generate_setter_arg(field, singular_name, item_ty);
+            (quote!(#singular_name: #arg_ty), value)
+        }
+        CollectionItem::Iterated => {
+            let item_ty = parse_quote!(<#ty as core::iter::IntoIterator>::Item);
+            let (arg_ty, value) = generate_setter_arg(field, singular_name, &item_ty);
+            (quote!(#singular_name: #arg_ty), value)
+        }
+        CollectionItem::KeyValue(key_ty, value_ty) => {
+            let key = format_ident!("key");
+            let value = format_ident!("value");
+            let (key_arg_ty, key) = generate_setter_arg(field, &key, key_ty);
+            let (value_arg_ty, value) = generate_setter_arg(field, &value, value_ty);
+            (
+                quote!(key: #key_arg_ty, value: #value_arg_ty),
+                quote!((#key, #value)),
+            )
+        }
+    };
+    Setter {
+        docs: generate_doc(format!("Adds one item to `{}`.", name.unraw())),
+        name: singular_name.clone(),
+        generics: quote!(),
+        args,
+        body: quote! {
+            core::iter::Extend::extend(&mut __builder.#name, core::iter::once(#item));
+        },
+        error: None,
+    }
+}
+
+/// Setter adding every item of an iterator to a collection, named after the
+/// field with an `extend_` prefix.
+fn generate_extend_setter(field: &Field) -> Setter {
+    let name = &field.ident;
+    let ty = field.ty;
+    let item_ty = match get_collection_item(ty) {
+        CollectionItem::Single(item_ty) => quote!(#item_ty),
+        CollectionItem::Iterated => quote!(<#ty as core::iter::IntoIterator>::Item),
+        CollectionItem::KeyValue(key_ty, value_ty) => quote!((#key_ty, #value_ty)),
+    };
+    Setter {
+        docs: generate_doc(format!(
+            "Adds every item of an iterator to `{}`.",
+            name.unraw()
+        )),
+        name: format_ident!("extend_{}", name),
+        generics: quote!(),
+        args: quote!(#name: impl core::iter::IntoIterator<Item = #item_ty>),
+        body: quote! {
+            core::iter::Extend::extend(&mut __builder.#name, #name);
+        },
+        error: None,
+    }
+}
+
+/// Arguments of the setter of the whole field, which sets a value of type
+/// `ty`, and the expression turning them into that value. With
+/// `setter(with = "...")` the arguments are passed to the user's function.
+fn generate_whole_setter_args(
+    field: &Field,
+    ty: &Type,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match &field.attrs.setter.with {
+        Some(SetterWith { path, args }) => {
+            let names = args.iter().map(|(name, _)| name);
+            let types = args.iter().map(|(_, ty)| ty);
+            let names_again = names.clone();
+            (
+                quote!(#(#names: #types),*),
+                quote!(#path(#(#names_again),*)),
+            )
+        }
+        None => {
+            let name = &field.ident;
+            let (arg_ty, value) = generate_setter_arg(field, name, ty);
+            (quote!(#name: #arg_ty), value)
+        }
+    }
+}
+
+/// Type of a setter's argument `arg` which sets a value of type `ty`, and the
+/// expression converting the argument into that value.
+fn generate_setter_arg(
+    field: &Field,
+    arg: &Ident,
+    ty: &Type,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if field.attrs.setter.into {
+        (
+            quote!(impl core::convert::Into<#ty>),
+            quote!(core::convert::Into::into(#arg)),
+        )
+    } else {
+        (quote!(#ty), quote!(#arg))
+    }
+}
+
+/// Wraps a setter into a method taking and returning the builder the way
+/// `pattern` calls for, with the given visibility. An
+/// immutable setter updates a copy of the builder made with `clone_builder`.
+/// A fallible setter returns the builder in `Ok`.
+fn generate_setter_method(
+    pattern: Pattern,
+    clone_builder: &proc_macro2::TokenStream,
+    vis: Option<&Visibility>,
+    setter: &Setter,
+) -> proc_macro2::TokenStream {
+    let Setter {
+        docs,
+        name,
+        generics,
+        args,
+        body,
+        error,
+    } = setter;
+    let (receiver, builder_ty, init) = match pattern {
+        Pattern::Mutable => (
+            quote!(&mut self),
+            quote!(&mut Self),
+            quote!(let __builder = self;),
+        ),
+        Pattern::Owned => (
+            quote!(self),
+            quote!(Self),
+            quote!(let mut __builder = self;),
+        ),
+        Pattern::Immutable => (
+            quote!(&self),
+            quote!(Self),
+            quote!(let mut __builder = #clone_builder;),
+        ),
+    };
+    let (ret, tail) = match error {
+        Some(error) => (
+            quote!(core::result::Result<#builder_ty, #error>),
+            quote!(core::result::Result::Ok(__builder)),
+        ),
+        None => (builder_ty, quote!(__builder)),
+    };
+    quote! {
+        #docs
+        #vis fn #name #generics(#receiver, #args) -> #ret {
+            #init
+            #body
+            #tail
+        }
+    }
+}
+
+/// Method laying another builder over this one: every field set in `other`
+/// replaces this builder's value, nested builders are merged in turn, and
+/// collections are replaced or appended to according to their `merge` option.
+fn generate_merge_method(
+    item: &Item,
+    builder: &Builder,
+    clone_builder: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let builder_ident = &builder.ident;
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let merged_fields = builder.fields.iter().map(|field| {
+        let name = &field.ident;
+        if field.sub_builder.is_some() {
             quote! {
-                if self.#name.is_none() {
-                    let e = std::string::String::from(std::format!("{} must be set", stringify!(#name))).into();
-                    return Err(e);
+                if let core::option::Option::Some(other) = other.#name {
+                    match &mut __builder.#name {
+                        core::option::Option::Some(builder) => {
+                            builder.merge(other);
+                        }
+                        none => *none = core::option::Option::Some(other),
+                    }
+                }
+            }
+        } else if field.is_collection() {
+            match field.attrs.merge.unwrap_or(Merge::Replace) {
+                Merge::Replace => quote! {
+                    let mut items = core::iter::IntoIterator::into_iter(other.#name).peekable();
+                    if items.peek().is_some() {
+                        __builder.#name = core::default::Default::default();
+                        core::iter::Extend::extend(&mut __builder.#name, items);
+                    }
+                },
+                Merge::Append => quote! {
+                    core::iter::Extend::extend(&mut __builder.#name, other.#name);
+                },
+            }
+        } else {
+            quote! {
+                if other.#name.is_some() {
+                    __builder.#name = other.#name;
                 }
             }
         }
     });
+    let setter = Setter {
+        docs: generate_doc(
+            "Lays `other` over this builder, whose fields set in `other` are replaced.".to_owned(),
+        ),
+        name: format_ident!("merge"),
+        generics: quote!(),
+        args: quote!(other: #builder_ident #ty_generics),
+        body: quote! {
+            let _ = &other;
+            #(#merged_fields)*
+        },
+        error: None,
+    };
+    generate_setter_method(
+        item.attrs.pattern,
+        clone_builder,
+        item.attrs.vis.as_ref(),
+        &setter,
+    )
+}
 
-    let set_fields = data_struct.fields.iter().map(|field| {
-        let name = field.ident.as_ref().unwrap();
-        if is_option_type(&field.ty) || is_vec_type(&field.ty) {
+/// Method filling every field that is not set yet from the environment
+/// variable named after the field with the struct's `env_prefix`, parsing the
+/// value with FromStr. A collection's variable holds its items separated by
+/// `env_separator`, with the entries of a map written as `key=value`. Nested
+/// builders and fields with `env = false` are left alone.
+fn generate_from_env_method(
+    item: &Item,
+    builder: &Builder,
+    clone_builder: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let Some(env_prefix) = &item.attrs.env_prefix else {
+        return quote!();
+    };
+    let env_error_ident = format_ident!("{}EnvError", builder.ident);
+    let filled_fields = builder.fields.iter().filter_map(|field| {
+        if field.sub_builder.is_some() || field.attrs.skip_env {
+            return None;
+        }
+        let name = &field.ident;
+        let ty = field.ty;
+        let var = format!("{}{}", env_prefix, name.unraw().to_string().to_uppercase());
+        let parse_error = quote! {
+            |error| #env_error_ident {
+                var: std::string::String::from(#var),
+                message: std::string::ToString::to_string(&error),
+            }
+        };
+        let filled = if field.is_collection() {
+            let separator = field
+                .attrs
+                .env_separator
+                .as_deref()
+                .or(item.attrs.env_separator.as_deref())
+                .unwrap_or(",");
+            let parse_item = match get_collection_item(ty) {
+                CollectionItem::Single(item_ty) => quote! {
+                    <#item_ty as core::str::FromStr>::from_str(item).map_err(#parse_error)?
+                },
+                CollectionItem::Iterated => quote! {
+                    <<#ty as core::iter::IntoIterator>::Item as core::str::FromStr>::from_str(item)
+                        .map_err(#parse_error)?
+                },
+                CollectionItem::KeyValue(key_ty, value_ty) => quote! {{
+                    let (key, value) = item.split_once('=').ok_or_else(|| #env_error_ident {
+                        var: std::string::String::from(#var),
+                        message: std::format!("expected `key=value`, found `{}`", item),
+                    })?;
+                    (
+                        <#key_ty as core::str::FromStr>::from_str(key).map_err(#parse_error)?,
+                        <#value_ty as core::str::FromStr>::from_str(value).map_err(#parse_error)?,
+                    )
+                }},
+            };
+            quote! {
+                if core::iter::IntoIterator::into_iter(&__builder.#name).next().is_none() {
+                    for item in value.split(#separator).filter(|item| !item.is_empty()) {
+                        let item = #parse_item;
+                        core::iter::Extend::extend(&mut __builder.#name, core::iter::once(item));
+                    }
+                }
+            }
+        } else {
+            let value_ty = field.option_type().unwrap_or(ty);
             quote! {
-                #name: self.#name.clone(),
+                if __builder.#name.is_none() {
+                    __builder.#name = core::option::Option::Some(
+                        <#value_ty as core::str::FromStr>::from_str(&value).map_err(#parse_error)?,
+                    );
+                }
+            }
+        };
+        Some(quote! {
+            match std::env::var(#var) {
+                core::result::Result::Ok(value) => {
+                    #filled
+                }
+                core::result::Result::Err(std::env::VarError::NotPresent) => {}
+                core::result::Result::Err(error) => return core::result::Result::Err((#parse_error)(error)),
+            }
+        })
+    });
+    let setter = Setter {
+        docs: generate_doc(format!(
+            "Fills the fields that are not set from the environment variables starting with `{env_prefix}`."
+        )),
+        name: format_ident!("from_env"),
+        generics: quote!(),
+        args: quote!(),
+        body: quote!(#(#filled_fields)*),
+        error: Some(quote!(#env_error_ident)),
+    };
+    generate_setter_method(
+        item.attrs.pattern,
+        clone_builder,
+        item.attrs.vis.as_ref(),
+        &setter,
+    )
+}
+
+/// Method giving mutable access to the builder of a `sub_builder` field, which
+/// is created when first needed.
+fn generate_sub_builder_method(
+    field: &Field,
+    sub_builder: &SubBuilder,
+) -> proc_macro2::TokenStream {
+    let name = &field.ident;
+    let setter_name = field.setter_name();
+    let docs = &field.docs;
+    let vis = &field.attrs.vis;
+    let ty = field.ty;
+    let sub_builder_ty = &sub_builder.ty;
+    quote! {
+        #(#docs)*
+        #vis fn #setter_name(&mut self) -> &mut #sub_builder_ty {
+            self.#name.get_or_insert_with(<#ty>::builder)
+        }
+    }
+}
+
+/// Expression copying the builder field by field, which only requires the
+/// field types to be Clone rather than the builder itself.
+fn generate_clone_builder(builder_ident: &Ident, fields: &[Field]) -> proc_macro2::TokenStream {
+    let names = fields.iter().map(|field| &field.ident);
+    quote! {
+        #builder_ident {
+            #(#names: self.#names.clone(),)*
+            __phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+/// Statements in `build` returning an error which lists every required field
+/// that was never set, including those of nested builders under the path of
+/// their `sub_builder` field.
+fn generate_missing_fields_check(item: &Item, builder: &Builder) -> proc_macro2::TokenStream {
+    let error_ident = &builder.error_ident;
+    if item.attrs.no_std {
+        return generate_array_missing_fields_check(builder);
+    }
+    let checks: Vec<_> = builder
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let name = &field.ident;
+            let field_name = name.unraw().to_string();
+            if let Some(sub_builder) = &field.sub_builder {
+                let ty = field.ty;
+                let sub_error = &sub_builder.error;
+                let result = format_ident!("__{}", name);
+                Some(quote! {
+                    // An unset nested builder is built from an empty one, without
+                    // storing it, so that `build` leaves the builder as it was.
+                    let #result = match self.#name.as_mut() {
+                        core::option::Option::Some(builder) => builder.build(),
+                        core::option::Option::None => <#ty>::builder().build(),
+                    };
+                    if let core::result::Result::Err(#sub_error::MissingFields(fields)) = &#result {
+                        __missing_fields.extend(fields.iter().map(|field| {
+                            std::format!("{}.{}", #field_name, field)
+                        }));
+                    }
+                })
+            } else if field.is_required() {
+                Some(quote! {
+                    if self.#name.is_none() {
+                        __missing_fields.push(std::string::String::from(#field_name));
+                    }
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    if checks.is_empty() {
+        return quote!();
+    }
+
+    quote! {
+        let mut __missing_fields = std::vec::Vec::new();
+        #(#checks)*
+        if !__missing_fields.is_empty() {
+            return Err(#error_ident::MissingFields(__missing_fields));
+        }
+    }
+}
+
+/// Missing fields check of a `no_std` builder, which lists the missing fields
+/// in an array with room for every required field.
+fn generate_array_missing_fields_check(builder: &Builder) -> proc_macro2::TokenStream {
+    let error_ident = &builder.error_ident;
+    let required_fields: Vec<&Ident> = builder
+        .fields
+        .iter()
+        .filter(|field| field.is_required())
+        .map(|field| &field.ident)
+        .collect();
+    if required_fields.is_empty() {
+        return quote!();
+    }
+    let len = required_fields.len();
+    let field_names = required_fields.iter().map(|name| name.unraw().to_string());
+
+    quote! {
+        let mut __missing_fields = [core::option::Option::None; #len];
+        let mut __missing_count = 0;
+        #(
+            if self.#required_fields.is_none() {
+                __missing_fields[__missing_count] =
+                    core::option::Option::Some(#field_names);
+                __missing_count += 1;
             }
+        )*
+        if __missing_count > 0 {
+            return Err(#error_ident::MissingFields(__missing_fields));
+        }
+    }
+}
+
+/// Resolves each field of the built item into a local variable named after the
+/// field and constructs the item out of them. Skipped fields are computed last,
+/// so their `default` expression can refer to any of the other fields.
+fn generate_construction(builder: &Builder, owned: bool) -> proc_macro2::TokenStream {
+    let error_ident = &builder.error_ident;
+    let resolved_fields = builder.fields.iter().map(|field| {
+        let name = &field.ident;
+        if field.sub_builder.is_some() {
+            // The nested builder was already built while looking for missing
+            // fields. Its error is rebuilt as this builder's error type, under
+            // the name of the field.
+            let result = format_ident!("__{}", name);
+            let field_name = name.unraw().to_string();
+            return quote! {
+                let #name = #result.map_err(|error| #error_ident::SubBuilder(
+                    #field_name,
+                    std::boxed::Box::new(error.__fold(
+                        #error_ident::MissingFields,
+                        #error_ident::Validation,
+                        |name, error| #error_ident::SubBuilder(name, std::boxed::Box::new(error)),
+                    )),
+                ))?;
+            };
+        }
+        let storage = if owned {
+            quote!(self.#name)
         } else {
+            quote!(self.#name.clone())
+        };
+        let value = generate_field_value(field, storage);
+        quote!(let #name = #value;)
+    });
+
+    let computed_fields = builder.skipped_fields.iter().map(|field| {
+        let name = &field.ident;
+        let value =
+            generate_default_value(field.attrs.default.as_ref().unwrap_or(&FieldDefault::Trait));
+        quote!(let #name = #value;)
+    });
+
+    let constructor = &builder.constructor;
+    let members = builder
+        .fields
+        .iter()
+        .chain(&builder.skipped_fields)
+        .map(|field| {
+            let member = &field.member;
+            let name = &field.ident;
+            quote!(#member: #name)
+        });
+
+    quote! {
+        #(#resolved_fields)*
+        #(#computed_fields)*
+        Ok(#constructor {
+            #(#members,)*
+        })
+    }
+}
+
+/// Final value of a field in `build`, given the expression `value` which yields
+/// the field's storage in the builder.
+fn generate_field_value(
+    field: &Field,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if field.is_option() || field.is_collection() {
+        value
+    } else if let Some(default) = &field.attrs.default {
+        let default = generate_default_value(default);
+        quote!(#value.unwrap_or_else(|| #default))
+    } else {
+        quote!(#value.unwrap())
+    }
+}
+
+/// Builder storage of a field which is set to the expression `value`.
+fn generate_stored_value(
+    field: &Field,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if let Some(sub_builder) = &field.sub_builder {
+        let sub_builder_ty = &sub_builder.ty;
+        quote!(core::option::Option::Some(<#sub_builder_ty>::from(#value)))
+    } else if field.is_option() || field.is_collection() {
+        value
+    } else {
+        quote!(core::option::Option::Some(#value))
+    }
+}
+
+/// Storage for each field in the builder. Option fields and collections are
+/// stored as they are, every other field is wrapped in an Option until it is
+/// set.
+fn generate_builder_fields(fields: &[Field]) -> Vec<proc_macro2::TokenStream> {
+    fields
+        .iter()
+        .map(|field| {
+            let name = &field.ident;
+            let ty = field.ty;
+            if let Some(sub_builder) = &field.sub_builder {
+                let sub_builder_ty = &sub_builder.ty;
+                quote! {
+                    #name: core::option::Option<#sub_builder_ty>,
+                }
+            } else if field.is_option() || field.is_collection() {
+                quote! {
+                    #name: #ty,
+                }
+            } else {
+                quote! {
+                    #name: core::option::Option<#ty>,
+                }
+            }
+        })
+        .collect()
+}
+
+/// `derive(...)` and `struct_attr(...)` attributes of the builder struct.
+fn generate_builder_attrs(struct_attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let derive = generate_derive(&struct_attrs.derives);
+    let attrs = &struct_attrs.struct_attrs;
+    quote! {
+        #derive
+        #(#[#attrs])*
+    }
+}
+
+fn generate_derive<P: quote::ToTokens>(derives: &[P]) -> proc_macro2::TokenStream {
+    if derives.is_empty() {
+        quote!()
+    } else {
+        quote!(#[derive(#(#derives),*)])
+    }
+}
+
+fn is_default_path(path: &Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Default")
+}
+
+/// Attributes making the builder deserializable, where every field is
+/// optional. Empty unless the struct asks for them with `#[builder(serde)]`.
+fn generate_serde_attrs(item: &Item, builder: &Builder) -> proc_macro2::TokenStream {
+    if !item.attrs.serde {
+        return quote!();
+    }
+    let container = &builder.serde;
+    quote! {
+        #[derive(::serde::Deserialize)]
+        #(#[serde(#container)])*
+    }
+}
+
+/// Serde options of each field of a deserializable builder, in the order of
+/// `generate_builder_fields`.
+fn generate_serde_field_attrs(item: &Item, fields: &[Field]) -> Vec<proc_macro2::TokenStream> {
+    fields
+        .iter()
+        .map(|field| {
+            if !item.attrs.serde {
+                return quote!();
+            }
+            let options = &field.serde;
             quote! {
-                #name: self.#name.as_ref().unwrap().clone(),
+                #[serde(default)]
+                #(#[serde(#options)])*
             }
+        })
+        .collect()
+}
+
+fn generate_field_inits(fields: &[Field]) -> Vec<proc_macro2::TokenStream> {
+    fields
+        .iter()
+        .map(|field| {
+            let name = &field.ident;
+            if field.is_collection() {
+                quote! {
+                    #name: core::default::Default::default(),
+                }
+            } else {
+                quote! {
+                    #name: core::option::Option::None,
+                }
+            }
+        })
+        .collect()
+}
+
+/// `const` for the function creating an empty builder, unless a collection
+/// needs `Default::default()`, which cannot be called in a const fn.
+fn generate_builder_fn_constness(fields: &[Field]) -> proc_macro2::TokenStream {
+    if fields.iter().any(Field::is_collection) {
+        quote!()
+    } else {
+        quote!(const)
+    }
+}
+
+fn generate_default_value(default: &FieldDefault) -> proc_macro2::TokenStream {
+    match default {
+        FieldDefault::Trait => quote!(core::default::Default::default()),
+        FieldDefault::Expr(expr) => quote!(#expr),
+    }
+}
+
+/// Arguments naming each of the item's generic parameters, for use where the
+/// builder's parameter list is extended beyond the item's own.
+fn generate_generic_args(generics: &Generics) -> Vec<proc_macro2::TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        })
+        .collect()
+}
+
+/// A `PhantomData` mentioning every lifetime and type parameter of the item, so
+/// that the builder uses each of them even if no builder field does.
+fn generate_phantom_type(generics: &Generics) -> proc_macro2::TokenStream {
+    let params = generics.params.iter().filter_map(|param| match param {
+        GenericParam::Lifetime(param) => {
+            let lifetime = &param.lifetime;
+            Some(quote!(&#lifetime ()))
         }
+        GenericParam::Type(param) => {
+            let ident = &param.ident;
+            Some(quote!(*const #ident))
+        }
+        GenericParam::Const(_) => None,
     });
+    quote! {
+        core::marker::PhantomData<fn() -> (#(#params,)*)>
+    }
+}
 
-    let build_method = quote! {
-        pub fn build(&mut self) -> std::result::Result<#item_ident, std::boxed::Box<dyn std::error::Error>> {
-            #(#field_set_checks)*
-            Ok(#item_ident {
-                #(#set_fields)*
-            })
+/// Error type of `from_env`, separate from the one of `build` which can never
+/// fail this way.
+fn generate_env_error(item: &Item, builder: &Builder) -> proc_macro2::TokenStream {
+    if item.attrs.env_prefix.is_none() {
+        return quote!();
+    }
+    let env_error_ident = format_ident!("{}EnvError", builder.ident);
+    quote! {
+        /// An environment variable read by `from_env` could not be read or
+        /// parsed.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #env_error_ident {
+            pub var: std::string::String,
+            pub message: std::string::String,
         }
-    };
 
-    Ok(quote! {
-        pub struct #builder_ident {
-            #(#fields)*
+        impl core::fmt::Display for #env_error_ident {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                core::write!(f, "{}: {}", self.var, self.message)
+            }
         }
 
-        impl #builder_ident {
-            #(#field_mutators)*
-            #build_method
+        impl core::error::Error for #env_error_ident {}
+    }
+}
+
+fn generate_error_enum(item: &Item, builder: &Builder) -> proc_macro2::TokenStream {
+    let error_ident = &builder.error_ident;
+    if item.attrs.no_std {
+        return generate_array_error_enum(builder);
+    }
+    quote! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum #error_ident {
+            /// Every required field that was never set, with the fields of
+            /// nested builders written as `server.port`.
+            MissingFields(std::vec::Vec<std::string::String>),
+            Validation(std::string::String),
+            /// The builder of a `sub_builder` field failed.
+            SubBuilder(&'static str, std::boxed::Box<#error_ident>),
         }
-    })
+
+        impl #error_ident {
+            /// Rebuilds the error out of the variants of another builder's
+            /// error type, which all have the same shape.
+            #[doc(hidden)]
+            pub fn __fold<E>(
+                self,
+                missing_fields: fn(std::vec::Vec<std::string::String>) -> E,
+                validation: fn(std::string::String) -> E,
+                sub_builder: fn(&'static str, E) -> E,
+            ) -> E {
+                match self {
+                    #error_ident::MissingFields(fields) => missing_fields(fields),
+                    #error_ident::Validation(message) => validation(message),
+                    #error_ident::SubBuilder(name, error) => {
+                        sub_builder(name, error.__fold(missing_fields, validation, sub_builder))
+                    }
+                }
+            }
+        }
+
+        impl core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self {
+                    #error_ident::MissingFields(fields) => {
+                        for (i, field) in fields.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(if i + 1 == fields.len() { " and " } else { ", " })?;
+                            }
+                            f.write_str(field)?;
+                        }
+                        f.write_str(" must be set")
+                    }
+                    #error_ident::Validation(message) => f.write_str(message),
+                    #error_ident::SubBuilder(name, error) => match **error {
+                        #error_ident::Validation(_) => core::write!(f, "{}: {}", name, error),
+                        _ => core::write!(f, "{}.{}", name, error),
+                    },
+                }
+            }
+        }
+
+        impl core::error::Error for #error_ident {}
+    }
 }
 
-fn get_data_struct<'a>(data: &'a Data, item_ident: &Ident) -> Result<&'a DataStruct, syn::Error> {
+/// Error type of a `no_std` builder, which does not allocate. Missing fields
+/// come first in an array with room for every required field, validation
+/// messages are static strings, and there are no nested builders.
+fn generate_array_error_enum(builder: &Builder) -> proc_macro2::TokenStream {
+    let error_ident = &builder.error_ident;
+    let len = builder
+        .fields
+        .iter()
+        .filter(|field| field.is_required())
+        .count();
+    quote! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum #error_ident {
+            /// The required fields that were never set, in the order they are
+            /// declared, followed by `None` for the others.
+            MissingFields([core::option::Option<&'static str>; #len]),
+            Validation(&'static str),
+        }
+
+        impl core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self {
+                    #error_ident::MissingFields(fields) => {
+                        let count = fields.iter().flatten().count();
+                        for (i, field) in fields.iter().flatten().enumerate() {
+                            if i > 0 {
+                                f.write_str(if i + 1 == count { " and " } else { ", " })?;
+                            }
+                            f.write_str(field)?;
+                        }
+                        f.write_str(" must be set")
+                    }
+                    #error_ident::Validation(message) => f.write_str(message),
+                }
+            }
+        }
+
+        impl core::error::Error for #error_ident {}
+    }
+}
+
+fn get_builders<'a>(data: &'a Data, item: &Item) -> Result<Vec<Builder<'a>>, syn::Error> {
+    let item_ident = item.ident;
     match data {
         Data::Struct(data_struct) => {
-            let is_tuple_struct = data_struct.fields.iter().any(|f| f.ident.is_none());
-            if is_tuple_struct {
-                return Err(syn::Error::new(
-                    item_ident.span(),
-                    "#[derive(Builder)] does not work for a tuple struct",
-                ));
+            let builder_ident = format_ident!("{item_ident}Builder");
+            let (skipped_fields, fields) = get_fields(&data_struct.fields, &item.attrs)?
+                .into_iter()
+                .partition(|field| field.attrs.skip);
+            Ok(vec![Builder {
+                error_ident: format_ident!("{builder_ident}Error"),
+                ident: builder_ident,
+                builder_fn: format_ident!("builder"),
+                constructor: quote!(#item_ident),
+                fields,
+                skipped_fields,
+                serde: item
+                    .serde
+                    .iter()
+                    .filter(|meta| !meta.path().is_ident("rename_all_fields"))
+                    .cloned()
+                    .collect(),
+            }])
+        }
+        Data::Enum(data_enum) => {
+            let mut builders = Vec::with_capacity(data_enum.variants.len());
+            for variant in &data_enum.variants {
+                let variant_ident = &variant.ident;
+                let builder_ident = format_ident!("{item_ident}{variant_ident}Builder");
+                let (skipped_fields, fields) = get_fields(&variant.fields, &item.attrs)?
+                    .into_iter()
+                    .partition(|field| field.attrs.skip);
+                // Fields of a variant are renamed by the variant's `rename_all`,
+                // or else by the enum's `rename_all_fields`.
+                let mut serde = parse_serde_attrs(&variant.attrs, &["rename_all"])?;
+                if serde.is_empty() {
+                    serde.extend(
+                        item.serde
+                            .iter()
+                            .filter(|meta| meta.path().is_ident("rename_all_fields"))
+                            .map(|meta| {
+                                let mut meta = meta.clone();
+                                match &mut meta {
+                                    Meta::Path(path) => *path = parse_quote!(rename_all),
+                                    Meta::List(list) => list.path = parse_quote!(rename_all),
+                                    Meta::NameValue(name_value) => {
+                                        name_value.path = parse_quote!(rename_all)
+                                    }
+                                }
+                                meta
+                            }),
+                    );
+                }
+                builders.push(Builder {
+                    error_ident: format_ident!("{builder_ident}Error"),
+                    ident: builder_ident,
+                    builder_fn: to_snake_case(variant_ident),
+                    constructor: quote!(#item_ident::#variant_ident),
+                    fields,
+                    skipped_fields,
+                    serde,
+                });
             }
-            Ok(data_struct)
+            Ok(builders)
         }
-        Data::Enum(_) => Err(syn::Error::new(
-            item_ident.span(),
-            "#[derive(Builder)] does not work for an enum",
-        )),
         Data::Union(_) => Err(syn::Error::new(
             item_ident.span(),
             "#[derive(Builder)] does not work for a union",
@@ -192,69 +1506,252 @@ fn get_data_struct<'a>(data: &'a Data, item_ident: &Ident) -> Result<&'a DataStr
     }
 }
 
-fn is_option_type(ty: &Type) -> bool {
-    get_option_underlying_type(ty).is_some()
+/// Name of the method returning a variant's builder, `TextMessage` becomes
+/// `text_message`.
+fn to_snake_case(ident: &Ident) -> Ident {
+    let chars: Vec<char> = ident.to_string().chars().collect();
+    let mut name = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            // A run of capitals is one word, such as `HTTP` in `HTTPRequest`,
+            // whose last capital may start the next word.
+            let starts_word = i > 0
+                && (!chars[i - 1].is_uppercase()
+                    || chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+            if starts_word {
+                name.push('_');
+            }
+            name.extend(ch.to_lowercase());
+        } else {
+            name.push(ch);
+        }
+    }
+    // Variants such as `Type` or `Move` turn into keywords, which are used as
+    // raw identifiers. The few that cannot be raw get a trailing underscore.
+    match name.as_str() {
+        "crate" | "self" | "super" => format_ident!("{}_", name, span = ident.span()),
+        _ => syn::parse_str(&name).unwrap_or_else(|_| Ident::new_raw(&name, ident.span())),
+    }
+}
+
+fn get_fields<'a>(
+    data_fields: &'a Fields,
+    struct_attrs: &StructAttrs,
+) -> Result<Vec<Field<'a>>, syn::Error> {
+    let mut fields = Vec::with_capacity(data_fields.len());
+    for (index, field) in data_fields.iter().enumerate() {
+        let mut attrs = parse_field_attrs(&field.attrs)?;
+        attrs.setter.inherit(&struct_attrs.setter);
+        if attrs.vis.is_none() {
+            attrs.vis.clone_from(&struct_attrs.vis);
+        }
+        // Tuple fields are named after their position unless given a name.
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let ident = match (&attrs.name, &field.ident) {
+            (Some(name), _) => name.clone(),
+            (None, Some(ident)) => ident.clone(),
+            (None, None) => format_ident!("_{index}"),
+        };
+        let mut field = Field {
+            ident,
+            member,
+            ty: &field.ty,
+            attrs,
+            docs: field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .collect(),
+            sub_builder: None,
+            serde: parse_serde_attrs(
+                &field.attrs,
+                &["rename", "alias", "skip", "skip_deserializing"],
+            )?,
+        };
+        if let Some(singular_name) = &field.attrs.each {
+            if *singular_name == field.setter_name() {
+                return Err(syn::Error::new(
+                    singular_name.span(),
+                    "`each` setter would have the same name as the setter of the whole field",
+                ));
+            }
+        }
+        if field.attrs.default.is_some()
+            && !field.attrs.skip
+            && (field.is_option() || field.is_collection())
+        {
+            return Err(syn::Error::new_spanned(
+                field.ty,
+                "`default` cannot be used on an Option field or a collection",
+            ));
+        }
+        if field.attrs.merge.is_some() && !field.is_collection() {
+            return Err(syn::Error::new_spanned(
+                field.ty,
+                "`merge` only applies to collections",
+            ));
+        }
+        if field.attrs.skip_env && struct_attrs.env_prefix.is_none() {
+            return Err(syn::Error::new_spanned(
+                field.ty,
+                "`env` requires `env_prefix` on the struct",
+            ));
+        }
+        if field.attrs.env_separator.is_some() {
+            if struct_attrs.env_prefix.is_none() {
+                return Err(syn::Error::new_spanned(
+                    field.ty,
+                    "`env_separator` requires `env_prefix` on the struct",
+                ));
+            }
+            if !field.is_collection() {
+                return Err(syn::Error::new_spanned(
+                    field.ty,
+                    "`env_separator` only applies to collections",
+                ));
+            }
+        }
+        if field.attrs.sub_builder {
+            field.sub_builder = Some(get_sub_builder(&field, struct_attrs)?);
+        }
+        fields.push(field);
+    }
+    Ok(fields)
+}
+
+/// Builder types of a `sub_builder` field, named after the last segment of the
+/// field's type.
+fn get_sub_builder(field: &Field, struct_attrs: &StructAttrs) -> Result<SubBuilder, syn::Error> {
+    if struct_attrs.typestate || struct_attrs.pattern != Pattern::Mutable {
+        return Err(syn::Error::new_spanned(
+            field.ty,
+            "`sub_builder` requires the mutable pattern",
+        ));
+    }
+    if struct_attrs.no_std {
+        return Err(syn::Error::new_spanned(
+            field.ty,
+            "`sub_builder` is not available with `no_std`",
+        ));
+    }
+    if field.is_option() || field.is_collection() || field.attrs.default.is_some() {
+        return Err(syn::Error::new_spanned(
+            field.ty,
+            "`sub_builder` cannot be used on an Option field, a collection or with `default`",
+        ));
+    }
+    let Type::Path(type_path) = field.ty else {
+        return Err(syn::Error::new_spanned(
+            field.ty,
+            "`sub_builder` requires a struct type deriving Builder",
+        ));
+    };
+    let mut ty = type_path.clone();
+    let segment = ty.path.segments.last_mut().unwrap();
+    segment.ident = format_ident!("{}Builder", segment.ident);
+    let mut error = ty.path.clone();
+    let segment = error.segments.last_mut().unwrap();
+    segment.ident = format_ident!("{}Error", segment.ident);
+    segment.arguments = PathArguments::None;
+    Ok(SubBuilder {
+        ty: Type::Path(ty),
+        error,
+    })
 }
 
 fn get_option_underlying_type(ty: &Type) -> Option<&Type> {
-    get_underlying_type(ty, "Option")
+    get_underlying_type(ty, "option", "Option")
 }
 
 fn get_vec_underlying_type(ty: &Type) -> Option<&Type> {
-    get_underlying_type(ty, "Vec")
+    get_underlying_type(ty, "vec", "Vec")
 }
 
-fn get_underlying_type<'t>(ty: &'t Type, type_name: &str) -> Option<&'t Type> {
-    if let Type::Path(type_path) = ty {
-        let segments = &type_path.path.segments;
-        if segments.len() == 1 {
-            let segment = &segments[0];
-            if let PathArguments::AngleBracketed(abga) = &segment.arguments {
-                if abga.args.len() == 1 && segment.ident == type_name {
-                    let arg = &abga.args[0];
-                    if let GenericArgument::Type(ty) = arg {
-                        return Some(ty);
-                    }
-                }
-            }
+/// The type argument of `ty` if it is the standard library type `type_name`,
+/// written either by itself or by its full path like `std::vec::Vec`.
+fn get_underlying_type<'t>(ty: &'t Type, module: &str, type_name: &str) -> Option<&'t Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+
+    let segments: Vec<_> = type_path.path.segments.iter().collect();
+    let segment = match segments[..] {
+        [segment] => segment,
+        [krate, module_segment, segment]
+            if ["std", "core", "alloc"]
+                .iter()
+                .any(|name| krate.ident == name)
+                && module_segment.ident == module =>
+        {
+            segment
         }
+        _ => return None,
+    };
+
+    if segment.ident != type_name {
+        return None;
+    }
+    let type_args = get_type_args(segment);
+    match type_args[..] {
+        [ty] => Some(ty),
+        _ => None,
     }
+}
 
-    None
+fn get_type_args(segment: &PathSegment) -> Vec<&Type> {
+    let PathArguments::AngleBracketed(abga) = &segment.arguments else {
+        return vec![];
+    };
+    abga.args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect()
 }
 
-struct Each {
-    name: Literal,
+/// What an `each` setter adds to a collection.
+enum CollectionItem<'t> {
+    Single(&'t Type),
+    /// An entry of a map, whose type is recognized by a name ending in `Map`.
+    KeyValue(&'t Type, &'t Type),
+    /// Any other collection is extended with the items it iterates over.
+    Iterated,
 }
 
-impl Parse for Each {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let each: Ident = input.parse()?;
-        if each != "each" {
-            return Err(syn::Error::new(each.span(), r#"expected `each`"#));
+fn get_collection_item(ty: &Type) -> CollectionItem<'_> {
+    const SEQUENCES: &[&str] = &[
+        "Vec",
+        "VecDeque",
+        "LinkedList",
+        "HashSet",
+        "BTreeSet",
+        "BinaryHeap",
+    ];
+
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            let name = segment.ident.to_string();
+            let type_args = get_type_args(segment);
+            if name.ends_with("Map") && type_args.len() >= 2 {
+                return CollectionItem::KeyValue(type_args[0], type_args[1]);
+            }
+            if SEQUENCES.contains(&name.as_str()) && !type_args.is_empty() {
+                return CollectionItem::Single(type_args[0]);
+            }
         }
-        input.parse::<Token![=]>()?;
-        let name: Literal = input.parse()?;
-        Ok(Each { name })
     }
+
+    CollectionItem::Iterated
 }
 
 fn is_vec_type(ty: &Type) -> bool {
     get_vec_underlying_type(ty).is_some()
 }
-
-fn get_singular_name(attrs: &[Attribute]) -> Result<Option<Ident>, syn::Error> {
-    for attr in attrs {
-        if attr.path().is_ident("builder") {
-            if let Meta::List(meta_list) = &attr.meta {
-                let Each { name } = parse2(meta_list.tokens.clone())?;
-                //TODO: find a better method of converting a string literal into ident without the surrounding doubel quotes
-                let name_str = name.to_string();
-                let name_str = &name_str[1..name_str.len() - 1];
-                let name_ident = format_ident!("{}", name_str);
-                return Ok(Some(name_ident));
-            }
-        }
-    }
-    Ok(None)
-}
@@ -18,9 +18,9 @@
 // attributes do not correspond to a macro invocation on their own; they are
 // simply looked at by other macro invocations.
 //
-// If the new one-at-a-time builder method is given the same name as the field,
-// avoid generating an all-at-once builder method for that field because the
-// names would conflict.
+// The all-at-once builder method named after the field is still generated next
+// to the one-at-a-time method, so the one-at-a-time method must be given a name
+// different from the field's.
 //
 //
 // Resources:
@@ -36,7 +36,7 @@ pub struct Command {
     #[builder(each = "arg")]
     args: Vec<String>,
     #[builder(each = "env")]
-    env: Vec<String>,
+    envs: Vec<String>,
     current_dir: Option<String>,
 }
 
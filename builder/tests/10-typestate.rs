@@ -0,0 +1,35 @@
+// With #[builder(typestate)] on the struct, the builder tracks in its type
+// which of the required fields have been set. Each required field gets a state
+// parameter that starts out as `CommandBuilderUnset` and becomes
+// `CommandBuilderSet` once the corresponding setter is called, and `build` only
+// exists on the builder type where every state parameter is `Set`.
+//
+// Setters consume the builder and return it with the updated state, so the
+// builder is used through method chaining.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    timeout: u64,
+}
+
+fn main() {
+    let command = Command::builder()
+        .timeout(30)
+        .arg("build".to_owned())
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, 30);
+}
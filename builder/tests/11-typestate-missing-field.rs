@@ -0,0 +1,15 @@
+// A typestate builder turns a missing required field into a compile error
+// instead of an error returned from `build` at runtime.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    timeout: u64,
+}
+
+fn main() {
+    let _ = Command::builder().executable("cargo".to_owned()).build();
+}
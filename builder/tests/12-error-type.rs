@@ -0,0 +1,42 @@
+// Instead of Box<dyn Error>, `build` returns a dedicated error type generated
+// next to the builder. Its name is the builder's name followed by `Error`, and
+// it has a `MissingFields` variant listing every required field that was not
+// set, so callers can match on it rather than compare strings.
+//
+//     #[derive(Debug, Clone, PartialEq, Eq)]
+//     pub enum CommandBuilderError {
+//         MissingFields(Vec<String>),
+//     }
+//
+// The error type implements Display and std::error::Error, so it still
+// converts into Box<dyn Error> with the `?` operator.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    current_dir: String,
+}
+
+fn build_boxed() -> Result<Command, Box<dyn Error>> {
+    let command = Command::builder().executable("cargo".to_owned()).build()?;
+    Ok(command)
+}
+
+fn main() {
+    let err = Command::builder()
+        .current_dir("..".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec!["executable".to_owned()]),
+    );
+    assert_eq!(err.to_string(), "executable must be set");
+
+    let err = build_boxed().err().unwrap();
+    assert_eq!(err.to_string(), "current_dir must be set");
+}
@@ -0,0 +1,57 @@
+// The builder carries over the generic parameters, bounds and where-clause of
+// the struct it builds, including lifetimes and const generics.
+//
+//     pub struct ConfigBuilder<'a, T: Clone> where T: Debug { ... }
+//
+//     impl<'a, T: Clone> ConfigBuilder<'a, T> where T: Debug { ... }
+//
+// A parameter that is not used by any of the builder's fields still needs to be
+// mentioned by the builder struct, so the builder holds a PhantomData of all of
+// them.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+#[derive(Builder)]
+pub struct Config<'a, T: Clone>
+where
+    T: Debug,
+{
+    name: &'a str,
+    value: T,
+    fallback: Option<T>,
+    #[builder(each = "tag")]
+    tags: Vec<&'a str>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Buffer<T: Clone + Default, const N: usize> {
+    fill: T,
+    label: Option<String>,
+}
+
+impl<T: Clone + Default, const N: usize> Buffer<T, N> {
+    fn to_array(&self) -> [T; N] {
+        std::array::from_fn(|_| self.fill.clone())
+    }
+}
+
+fn main() {
+    let name = String::from("retries");
+    let config = Config::builder()
+        .name(&name)
+        .value(3u8)
+        .tag("network")
+        .build()
+        .unwrap();
+
+    assert_eq!(config.name, "retries");
+    assert_eq!(config.value, 3);
+    assert_eq!(config.fallback, None);
+    assert_eq!(config.tags, vec!["network"]);
+
+    let buffer = Buffer::<u8, 4>::builder().fill(7).build().unwrap();
+    assert_eq!(buffer.to_array(), [7, 7, 7, 7]);
+    assert_eq!(buffer.label, None);
+}
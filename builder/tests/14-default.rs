@@ -0,0 +1,45 @@
+// A field marked #[builder(default)] no longer has to be set before calling
+// `build`. If its setter was never called, the field is initialized with
+// Default::default(). With #[builder(default = "...")] the string literal is
+// parsed as an expression which is evaluated instead.
+//
+// Defaulted fields are not required fields, so a typestate builder does not
+// track them in its type either.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    current_dir: String,
+    #[builder(default = "30")]
+    timeout: u64,
+    #[builder(default = r#"String::from("info")"#)]
+    log_level: String,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Server {
+    host: String,
+    #[builder(default = "8080")]
+    port: u16,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .timeout(60)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir, "");
+    assert_eq!(command.timeout, 60);
+    assert_eq!(command.log_level, "info");
+
+    let server = Server::builder().host("localhost".to_owned()).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+}
@@ -0,0 +1,58 @@
+// By default setters take `&mut self` and `build` clones every field out of the
+// builder, which requires all field types to be Clone.
+//
+// With #[builder(pattern = "owned")] the setters take the builder by value and
+// return it, and `build` consumes the builder and moves the fields out of it.
+// This works for field types that are not Clone.
+//
+// With #[builder(pattern = "immutable")] the setters take `&self` and return an
+// updated copy of the builder, leaving the original unchanged so that it can be
+// reused as a template.
+
+use derive_builder::Builder;
+
+pub struct Connection {
+    addr: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Client {
+    connection: Connection,
+    on_error: Box<dyn Fn(&str) -> String>,
+    #[builder(each = "header")]
+    headers: Vec<String>,
+    retries: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+fn main() {
+    let client = Client::builder()
+        .connection(Connection {
+            addr: "localhost:80".to_owned(),
+        })
+        .on_error(Box::new(|message| format!("error: {}", message)))
+        .header("Accept: */*".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(client.connection.addr, "localhost:80");
+    assert_eq!((client.on_error)("timeout"), "error: timeout");
+    assert_eq!(client.headers, vec!["Accept: */*"]);
+    assert_eq!(client.retries, None);
+
+    let template = Command::builder().executable("cargo".to_owned());
+    let build = template.arg("build".to_owned());
+    let test = template.arg("test".to_owned());
+
+    assert_eq!(build.build().unwrap().args, vec!["build"]);
+    assert_eq!(test.build().unwrap().args, vec!["test"]);
+    assert!(template.build().unwrap().args.is_empty());
+}
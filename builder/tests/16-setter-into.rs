@@ -0,0 +1,58 @@
+// With #[builder(setter(into))] on a field, its setter accepts any value that
+// converts into the field type, so a String field can be set from a &str.
+//
+//     fn executable(&mut self, executable: impl Into<String>) -> &mut Self
+//
+// For a Vec field with `each`, the one-at-a-time setter converts into the
+// element type instead, and for an Option field into the type inside the
+// Option. Putting #[builder(setter(into))] on the struct applies it to every
+// field.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(into))]
+    current_dir: Option<PathBuf>,
+    timeout: u64,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into))]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(each = "alias")]
+    aliases: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..")
+        .timeout(30)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("..")));
+
+    let server = Server::builder()
+        .host("localhost")
+        .port(8080u16)
+        .alias("local")
+        .build()
+        .unwrap();
+
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.aliases, vec!["local"]);
+}
@@ -0,0 +1,59 @@
+// #[builder(build_fn(validate = "..."))] names a function that `build` calls
+// with a reference to the builder once every required field is known to be set
+// and before the struct is constructed. The function returns
+// Result<(), String>, and an error from it is returned by `build` as the
+// `Validation` variant of the builder's error type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(build_fn(validate = "validation::check_range"))]
+pub struct Range {
+    min: u32,
+    max: u32,
+}
+
+mod validation {
+    use super::RangeBuilder;
+
+    pub fn check_range(builder: &RangeBuilder) -> Result<(), String> {
+        match (builder.min, builder.max) {
+            (Some(min), Some(max)) if min > max => {
+                Err(format!("min ({}) must not exceed max ({})", min, max))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned", build_fn(validate = "check_port"))]
+pub struct Server {
+    port: u16,
+}
+
+fn check_port(builder: &ServerBuilder) -> Result<(), String> {
+    if builder.port == Some(0) {
+        return Err("port must not be 0".to_owned());
+    }
+    Ok(())
+}
+
+fn main() {
+    let range = Range::builder().min(1).max(10).build().unwrap();
+    assert_eq!((range.min, range.max), (1, 10));
+
+    let err = Range::builder().min(10).max(1).build().err().unwrap();
+    assert_eq!(
+        err,
+        RangeBuilderError::Validation("min (10) must not exceed max (1)".to_owned()),
+    );
+    assert_eq!(err.to_string(), "min (10) must not exceed max (1)");
+
+    let err = Range::builder().min(10).build().err().unwrap();
+    assert_eq!(err, RangeBuilderError::MissingFields(vec!["max".to_owned()]));
+
+    assert!(Server::builder().port(8080).build().is_ok());
+    let err = Server::builder().port(0).build().err().unwrap();
+    assert_eq!(err.to_string(), "port must not be 0");
+}
@@ -0,0 +1,95 @@
+// Deriving Builder on an enum generates one builder per variant. The builder
+// for a variant is named after the enum and the variant, and is returned by a
+// method on the enum named after the variant in snake_case, where a run of
+// capitals such as `HTTP` in `HTTPRequest` counts as one word. A name that is
+// a keyword is written as a raw identifier, like `r#type()` for `Type`, except
+// for `crate`, `self` and `super` which get a trailing underscore instead.
+//
+//     impl Message {
+//         pub fn text_message() -> MessageTextMessageBuilder { ... }
+//     }
+//
+// Each builder has the same setters, `each` and `default` handling as the
+// builder of a struct, and its `build` method returns the enum.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Message {
+    Ping {
+        id: u32,
+    },
+    TextMessage {
+        body: String,
+        #[builder(each = "tag")]
+        tags: Vec<String>,
+        #[builder(default = "1")]
+        priority: u8,
+    },
+    Shutdown,
+    HTTPRequest {
+        path: String,
+    },
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub enum Shape {
+    Circle { radius: f64 },
+    Type { name: String },
+    Crate { name: String },
+}
+
+fn main() {
+    let ping = Message::ping().id(7).build().unwrap();
+    assert_eq!(ping, Message::Ping { id: 7 });
+
+    let text = Message::text_message()
+        .body("hello".to_owned())
+        .tag("greeting".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        text,
+        Message::TextMessage {
+            body: "hello".to_owned(),
+            tags: vec!["greeting".to_owned()],
+            priority: 1,
+        },
+    );
+
+    let err = Message::ping().build().err().unwrap();
+    assert_eq!(err, MessagePingBuilderError::MissingFields(vec!["id".to_owned()]));
+
+    assert_eq!(Message::shutdown().build().unwrap(), Message::Shutdown);
+
+    let request = Message::http_request()
+        .path("/".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        Message::HTTPRequest {
+            path: "/".to_owned(),
+        },
+    );
+
+    let circle = Shape::circle().radius(1.5).build().unwrap();
+    assert_eq!(circle, Shape::Circle { radius: 1.5 });
+
+    let boxed = Shape::crate_().name("box".to_owned()).build().unwrap();
+    assert_eq!(
+        boxed,
+        Shape::Crate {
+            name: "box".to_owned(),
+        },
+    );
+
+    let named = Shape::r#type().name("square".to_owned()).build().unwrap();
+    assert_eq!(
+        named,
+        Shape::Type {
+            name: "square".to_owned(),
+        },
+    );
+}
@@ -0,0 +1,45 @@
+// Tuple structs get a builder too. Their setters are named after the position
+// of the field, `_0`, `_1` and so on, unless the field is given a name with
+// #[builder(name = "...")]. The same applies to tuple variants of an enum.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Meters(f64);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Size(
+    #[builder(name = "width")] u32,
+    #[builder(name = "height")] u32,
+    #[builder(default)] bool,
+);
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Pair(String, #[builder(each = "item")] Vec<u8>);
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Event {
+    Click(#[builder(name = "x")] i32, #[builder(name = "y")] i32),
+    Key(char),
+}
+
+fn main() {
+    let meters = Meters::builder()._0(1.5).build().unwrap();
+    assert_eq!(meters, Meters(1.5));
+
+    let size = Size::builder().width(640).height(480).build().unwrap();
+    assert_eq!(size, Size(640, 480, false));
+
+    let err = Size::builder().width(640).build().err().unwrap();
+    assert_eq!(err.to_string(), "height must be set");
+
+    let pair = Pair::builder()._0("bytes".to_owned()).item(1).item(2).build().unwrap();
+    assert_eq!(pair, Pair("bytes".to_owned(), vec![1, 2]));
+
+    let click = Event::click().x(10).y(20).build().unwrap();
+    assert_eq!(click, Event::Click(10, 20));
+
+    let key = Event::key()._0('q').build().unwrap();
+    assert_eq!(key, Event::Key('q'));
+}
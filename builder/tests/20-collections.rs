@@ -0,0 +1,75 @@
+// `each` is not limited to Vec. Any collection that implements Default and
+// Extend can be filled in one item at a time. The builder stores the collection
+// itself, starting out empty.
+//
+// For the standard library collections, and for Vec and Option written with
+// their full path like `std::vec::Vec<T>`, the setter takes the element type.
+// Maps, recognized by a type name ending in `Map`, get a setter taking the key
+// and the value. For any other collection the setter takes the type the
+// collection iterates over:
+//
+//     fn item(&mut self, item: <Ring as IntoIterator>::Item) -> &mut Self
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+#[derive(Clone, Default)]
+pub struct Ring {
+    items: Vec<u8>,
+}
+
+impl Extend<u8> for Ring {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+impl IntoIterator for Ring {
+    type Item = u8;
+    type IntoIter = std::vec::IntoIter<u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(each = "header", setter(into))]
+    headers: HashMap<String, String>,
+    #[builder(each = "param")]
+    params: BTreeMap<&'static str, i32>,
+    #[builder(each = "flag")]
+    flags: HashSet<char>,
+    #[builder(each = "step")]
+    steps: VecDeque<u32>,
+    #[builder(each = "arg")]
+    args: std::vec::Vec<String>,
+    #[builder(each = "byte")]
+    ring: Ring,
+    timeout: std::option::Option<u64>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .header("Accept", "*/*")
+        .header("Host", "localhost")
+        .param("page", 2)
+        .flag('v')
+        .flag('v')
+        .step(1)
+        .step(2)
+        .arg("--verbose".to_owned())
+        .byte(0xff)
+        .build()
+        .unwrap();
+
+    assert_eq!(request.headers.len(), 2);
+    assert_eq!(request.headers["Host"], "localhost");
+    assert_eq!(request.params.get("page"), Some(&2));
+    assert_eq!(request.flags.len(), 1);
+    assert_eq!(request.steps, VecDeque::from([1, 2]));
+    assert_eq!(request.args, vec!["--verbose"]);
+    assert_eq!(request.ring.items, vec![0xff]);
+    assert_eq!(request.timeout, None);
+}
@@ -0,0 +1,39 @@
+// A collection field with `each` gets three setters: one named after the field
+// which replaces the whole collection, the one-at-a-time setter named by
+// `each`, and one prefixed with `extend_` which adds every item of an iterator.
+//
+//     fn args(&mut self, args: Vec<String>) -> &mut Self
+//     fn arg(&mut self, arg: String) -> &mut Self
+//     fn extend_args(&mut self, args: impl IntoIterator<Item = String>) -> &mut Self
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "var")]
+    env: HashMap<String, String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("check".to_owned())
+        .args(vec!["build".to_owned()])
+        .arg("--release".to_owned())
+        .extend_args(["--locked".to_owned(), "--offline".to_owned()])
+        .var("RUST_LOG".to_owned(), "info".to_owned())
+        .extend_env([("CARGO_TERM_COLOR".to_owned(), "never".to_owned())])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        command.args,
+        vec!["build", "--release", "--locked", "--offline"],
+    );
+    assert_eq!(command.env.len(), 2);
+    assert_eq!(command.env["RUST_LOG"], "info");
+}
@@ -0,0 +1,13 @@
+// Because the setter for the whole collection is named after the field, the
+// one-at-a-time setter given by `each` cannot have the same name.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "env")]
+    env: Vec<String>,
+}
+
+fn main() {}
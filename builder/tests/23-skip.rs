@@ -0,0 +1,52 @@
+// A field marked #[builder(skip)] is left out of the builder entirely: it has
+// no setter and no storage, and `build` initializes it with Default::default()
+// or with its `default` expression.
+//
+// Skipped fields are computed after all other fields have been resolved, and
+// each of those is available to the `default` expression as a variable of the
+// same name. This makes it possible to derive a value such as a cached hash
+// from the fields that were set on the builder.
+
+use derive_builder::Builder;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Builder)]
+pub struct Key {
+    namespace: String,
+    #[builder(default = "1")]
+    version: u32,
+    #[builder(skip, default = "hash_of(&(&namespace, version))")]
+    hash: u64,
+    #[builder(skip)]
+    hits: Vec<u64>,
+    #[builder(skip, default = "Some(namespace.len())")]
+    namespace_len: Option<usize>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Area(
+    #[builder(name = "width")] u32,
+    #[builder(name = "height")] u32,
+    #[builder(skip, default = "width * height")] u32,
+);
+
+fn main() {
+    let key = Key::builder().namespace("users".to_owned()).build().unwrap();
+
+    assert_eq!(key.namespace, "users");
+    assert_eq!(key.version, 1);
+    assert_eq!(key.hash, hash_of(&("users", 1u32)));
+    assert!(key.hits.is_empty());
+    assert_eq!(key.namespace_len, Some(5));
+
+    let area = Area::builder().width(3).height(4).build().unwrap();
+    assert_eq!(area.2, 12);
+}
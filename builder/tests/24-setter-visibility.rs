@@ -0,0 +1,60 @@
+// Setters are private to the module defining the struct unless given a
+// visibility. #[builder(vis = "pub(crate)")] on the struct makes every setter
+// visible to the rest of the crate, and `vis` on a field overrides that for
+// the field's own setters.
+//
+// #[builder(setter(prefix = "with_"))] renames the setter of each field, so
+// that the setter for `port` becomes `with_port`. The prefix can also be put
+// on a single field.
+//
+// Doc comments on a field are copied onto the setter of the whole field. Its
+// other setters, such as `clear_` or the `each` setter, get a generated line
+// saying what they do.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(vis = "pub(crate)", setter(prefix = "with_"))]
+    pub struct Server {
+        /// Host name the server binds to.
+        pub host: String,
+        /// Port the server listens on.
+        pub port: u16,
+        #[builder(each = "alias")]
+        pub aliases: Vec<String>,
+        #[builder(vis = "pub", setter(prefix = "set_"))]
+        pub timeout: Option<u64>,
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate, vis = "pub")]
+    pub struct Client {
+        /// Address of the server.
+        pub address: String,
+    }
+}
+
+use config::{Client, Server};
+
+fn main() {
+    let server = Server::builder()
+        .with_host("localhost".to_owned())
+        .with_port(8080)
+        .alias("local".to_owned())
+        .set_timeout(30)
+        .build()
+        .unwrap();
+
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.aliases, vec!["local"]);
+    assert_eq!(server.timeout, Some(30));
+
+    let client = Client::builder()
+        .address("localhost:8080".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(client.address, "localhost:8080");
+}
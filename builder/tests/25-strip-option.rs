@@ -0,0 +1,106 @@
+// The setter of an Option field takes the value inside the Option. With
+// #[builder(setter(strip_option = false))] it takes the whole Option instead,
+// so the field can be set from a value that may be None.
+//
+//     fn timeout(&mut self, timeout: Option<u64>) -> &mut Self
+//
+// Every Option field also gets a `clear_` method which resets it to None.
+//
+// Option fields are recognized whether written as `Option<T>` or by the full
+// path `std::option::Option<T>`. A field of type `Option<Option<T>>` has a
+// setter taking `Option<T>`, so that setting it to `None` is different from
+// never setting it.
+//
+// A type that is only named `Option`, such as a type alias, is mistaken for
+// one. #[builder(opaque)] on the field makes the builder treat its type like
+// any other: the setter takes the whole value and the field is required
+// unless it has a `default`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(setter(strip_option = false))]
+    timeout: Option<u64>,
+    current_dir: std::option::Option<String>,
+    exit_code: Option<Option<i32>>,
+}
+
+#[derive(Builder)]
+#[builder(setter(strip_option = false))]
+pub struct Server {
+    host: Option<String>,
+    #[builder(setter(strip_option))]
+    port: Option<u16>,
+}
+
+mod alias {
+    use derive_builder::Builder;
+
+    pub type Option<T> = Result<T, String>;
+
+    #[derive(Builder)]
+    #[builder(vis = "pub")]
+    pub struct Lookup {
+        #[builder(opaque)]
+        pub address: Option<u32>,
+        #[builder(opaque, default = "Err(String::new())")]
+        pub fallback: Option<u32>,
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .timeout(Some(30))
+        .current_dir("..".to_owned())
+        .exit_code(None)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.timeout, Some(30));
+    assert_eq!(command.current_dir, Some("..".to_owned()));
+    assert_eq!(command.exit_code, Some(None));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .timeout(Some(30))
+        .clear_timeout()
+        .current_dir("..".to_owned())
+        .clear_current_dir()
+        .build()
+        .unwrap();
+
+    assert_eq!(command.timeout, None);
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.exit_code, None);
+
+    // Like any Option field, `current_dir` may be left unset.
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.current_dir, None);
+
+    let server = Server::builder()
+        .host(None)
+        .port(8080)
+        .build()
+        .unwrap();
+
+    assert_eq!(server.host, None);
+    assert_eq!(server.port, Some(8080));
+
+    let lookup = alias::Lookup::builder()
+        .address(Err("not found".to_owned()))
+        .build()
+        .unwrap();
+
+    assert_eq!(lookup.address, Err("not found".to_owned()));
+    assert_eq!(lookup.fallback, Err(String::new()));
+
+    let err = alias::Lookup::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "address must be set");
+}
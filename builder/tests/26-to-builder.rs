@@ -0,0 +1,66 @@
+// An existing struct converts into a builder with every field already set,
+// through `From<Command> for CommandBuilder`, so that a few fields can be
+// changed before building a new struct.
+//
+// When the builder uses the mutable or immutable pattern, which already
+// requires the fields to be Clone, `to_builder(&self)` does the same without
+// consuming the struct.
+//
+// A typestate builder created this way starts out with every required field
+// set, so `build` can be called right away.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "30")]
+    timeout: u64,
+    #[builder(skip, default = "args.len()")]
+    arg_count: usize,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+
+    let release = command
+        .to_builder()
+        .arg("--release".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(release.executable, "cargo");
+    assert_eq!(release.args, vec!["build", "--release"]);
+    assert_eq!(release.current_dir, Some("..".to_owned()));
+    assert_eq!(release.timeout, 30);
+    assert_eq!(release.arg_count, 2);
+
+    let rebuilt = CommandBuilder::from(command).timeout(60).build().unwrap();
+    assert_eq!(rebuilt.args, vec!["build"]);
+    assert_eq!(rebuilt.timeout, 60);
+
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .build()
+        .unwrap();
+
+    let server = ServerBuilder::from(server).port(9090).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 9090);
+}
@@ -0,0 +1,102 @@
+// A field whose type derives Builder itself can be marked
+// #[builder(sub_builder)]. Instead of a setter taking the whole value, the
+// outer builder gets a method returning the field's own builder, which is
+// created the first time it is asked for.
+//
+//     fn server(&mut self) -> &mut ServerBuilder
+//
+// The outer `build` builds the nested struct too. Fields missing from the
+// nested builder are reported with the path to them, like "server.port must
+// be set", and any other error of the nested builder as a SubBuilder error
+// naming the field.
+//
+// Building does not change the builder: a nested builder that was never asked
+// for stays unset. The nested struct does not have to be Clone. `to_builder` turns it into a
+// builder with its own `to_builder`.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(derive(Clone, Debug, PartialEq))]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(default)]
+    workers: usize,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(derive(Clone, Debug, PartialEq))]
+pub struct Logging {
+    #[builder(default = "\"info\".to_owned()")]
+    level: String,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(derive(Clone, Debug, PartialEq))]
+pub struct Config {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+    #[builder(sub_builder)]
+    logging: Logging,
+}
+
+#[derive(Builder, Debug)]
+pub struct Deployment {
+    #[builder(sub_builder)]
+    config: Config,
+}
+
+fn main() {
+    let mut builder = Config::builder();
+    builder.name("app".to_owned());
+    builder.server().host("localhost".to_owned()).port(8080);
+    builder.server().workers(4);
+    let config = builder.build().unwrap();
+
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.server.workers, 4);
+    assert_eq!(config.logging.level, "info");
+
+    let mut builder = config.to_builder();
+    builder.server().port(9090);
+    let config = builder.build().unwrap();
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 9090);
+
+    let mut builder = Config::builder();
+    builder.name("app".to_owned());
+    builder.server().host("localhost".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields(vec!["server.port".to_owned()]),
+    );
+    assert_eq!(err.to_string(), "server.port must be set");
+
+    let mut builder = Config::builder();
+    builder.name("app".to_owned());
+    let unchanged = builder.clone();
+    builder.build().unwrap_err();
+    assert_eq!(builder, unchanged);
+
+    let mut builder = Deployment::builder();
+    builder.config().name("app".to_owned());
+    builder.config().server().host("localhost".to_owned()).port(80);
+    let deployment = builder.build().unwrap();
+    let mut builder = deployment.to_builder();
+    builder.config().server().port(443);
+    let deployment = builder.build().unwrap();
+    assert_eq!(deployment.config.name, "app");
+    assert_eq!(deployment.config.server.port, 443);
+
+    let mut builder = Deployment::builder();
+    builder.config().name("app".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "config.server.host and config.server.port must be set",
+    );
+}
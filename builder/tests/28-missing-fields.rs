@@ -0,0 +1,54 @@
+// `build` does not stop at the first required field that was not set. It
+// checks all of them and returns one MissingFields error listing every
+// missing field, in the order they are declared.
+//
+// The error message names all of them too: "a must be set" for one field,
+// "a and b must be set" for two, "a, b and c must be set" for more. A field
+// with a raw identifier like `r#type` is named without its `r#`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: String,
+    timeout: u64,
+    env: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Resource {
+    r#type: String,
+    name: String,
+}
+
+fn main() {
+    let err = Command::builder().build().err().unwrap();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec![
+            "executable".to_owned(),
+            "current_dir".to_owned(),
+            "timeout".to_owned(),
+        ]),
+    );
+    assert_eq!(
+        err.to_string(),
+        "executable, current_dir and timeout must be set",
+    );
+
+    let err = Command::builder()
+        .current_dir("..".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    let CommandBuilderError::MissingFields(fields) = &err else {
+        panic!("expected missing fields, got {err:?}");
+    };
+    assert_eq!(fields, &["executable", "timeout"]);
+    assert_eq!(err.to_string(), "executable and timeout must be set");
+
+    let err = Resource::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "type and name must be set");
+}
@@ -0,0 +1,85 @@
+// With the `serde` feature of this crate, #[builder(serde)] makes the builder
+// derive serde::Deserialize, so partial configuration can be loaded straight
+// into a builder and completed with setters before calling `build`. Builders
+// of structs without it are unaffected, so their fields need not implement
+// Deserialize.
+//
+// Each field of the builder may be left out of the input. Renames on the
+// struct are repeated on the builder, so both read the same input: the
+// `rename`, `alias` and `skip` options of a field, and `rename_all` and
+// `deny_unknown_fields` on the struct. A field skipped by serde can still be
+// set on the builder, which is how a field whose type does not implement
+// Deserialize is handled. A `sub_builder` field deserializes into its nested
+// builder, which needs #[builder(serde)] too.
+//
+// The crate using the derive needs serde with its `derive` feature among its
+// own dependencies.
+
+use derive_builder::Builder;
+use serde::Deserialize;
+
+#[derive(Builder, Clone, Debug, PartialEq, Deserialize)]
+#[builder(serde)]
+#[serde(rename_all = "kebab-case")]
+pub struct Server {
+    host_name: String,
+    port: u16,
+    #[serde(rename = "worker-count", alias = "workers")]
+    #[builder(default = "1")]
+    worker_count: usize,
+}
+
+#[derive(Builder, Debug, Deserialize)]
+#[builder(serde)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    name: String,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    #[builder(sub_builder)]
+    server: Server,
+    #[serde(skip)]
+    handle: Option<Handle>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Handle(u32);
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Log {
+    file: std::fs::File,
+}
+
+fn main() {
+    let mut builder: ConfigBuilder = serde_json::from_str(
+        r#"{
+            "tags": ["web"],
+            "server": { "host-name": "localhost", "workers": 4 }
+        }"#,
+    )
+    .unwrap();
+    builder.name("app".to_owned()).tag("prod".to_owned());
+    builder.server().port(8080);
+    builder.handle(Handle(7));
+    let config = builder.build().unwrap();
+
+    assert_eq!(config.name, "app");
+    assert_eq!(config.tags, vec!["web", "prod"]);
+    assert_eq!(config.server.host_name, "localhost");
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.server.worker_count, 4);
+    assert_eq!(config.handle, Some(Handle(7)));
+
+    let mut builder: ConfigBuilder = serde_json::from_str("{}").unwrap();
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "name, server.host_name and server.port must be set",
+    );
+
+    assert!(serde_json::from_str::<ConfigBuilder>(r#"{ "nmae": "app" }"#).is_err());
+
+    let err = Log::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "file must be set");
+}
@@ -0,0 +1,85 @@
+// `merge` lays another builder over this one, for configuration that comes in
+// layers like defaults, then a file, then the command line. Every field set in
+// the other builder replaces the value in this one, and fields it leaves unset
+// keep theirs.
+//
+//     fn merge(&mut self, other: CommandBuilder) -> &mut Self
+//
+// A collection is replaced by the other builder's when that one has any
+// items. With #[builder(merge = "append")] the other builder's items are added
+// after these instead. Nested `sub_builder` fields are merged in turn.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env", merge = "append")]
+    envs: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "30")]
+    timeout: u64,
+}
+
+#[derive(Builder, Clone)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Limits {
+    memory: u64,
+    cpus: Option<u32>,
+}
+
+fn main() {
+    let mut defaults = Command::builder();
+    defaults
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG=info".to_owned())
+        .current_dir("..".to_owned());
+
+    let mut overrides = Command::builder();
+    overrides
+        .arg("test".to_owned())
+        .env("RUST_BACKTRACE=1".to_owned())
+        .timeout(60);
+
+    let command = defaults.merge(overrides).build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["test"]);
+    assert_eq!(command.envs, vec!["RUST_LOG=info", "RUST_BACKTRACE=1"]);
+    assert_eq!(command.current_dir, Some("..".to_owned()));
+    assert_eq!(command.timeout, 60);
+
+    let command = defaults.merge(Command::builder()).build().unwrap();
+    assert_eq!(command.args, vec!["test"]);
+
+    let mut base = Config::builder();
+    base.server().host("localhost".to_owned()).port(8080);
+    let mut layer = Config::builder();
+    layer.server().port(9090);
+    let config = base.merge(layer).build().unwrap();
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 9090);
+
+    let limits = Limits::builder()
+        .memory(512)
+        .cpus(2)
+        .merge(Limits::builder().memory(1024))
+        .build()
+        .unwrap();
+    assert_eq!(limits.memory, 1024);
+    assert_eq!(limits.cpus, Some(2));
+}
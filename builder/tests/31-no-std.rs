@@ -0,0 +1,107 @@
+// With #[builder(no_std)] the generated code only refers to `core`, for crates
+// built with #![no_std]. The error type does not allocate: MissingFields holds
+// an array with room for every required field, in which the missing ones come
+// first, and a `build_fn(validate)` function returns its message as a
+// `&'static str`. Nested `sub_builder` fields are not available.
+//
+//     pub enum ConfigBuilderError {
+//         MissingFields([Option<&'static str>; 2]),
+//         Validation(&'static str),
+//     }
+//
+// Independently of `no_std`, the function creating an empty builder is a
+// const fn whenever the struct has no collection, so a builder can be put in a
+// const or a static.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as _;
+
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(no_std, build_fn(validate = "Self::validate"))]
+pub struct Config {
+    baud_rate: u32,
+    address: u8,
+    retries: Option<u8>,
+    #[builder(default = "8")]
+    data_bits: u8,
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_std)]
+pub struct Frame {
+    r#type: u8,
+}
+
+impl ConfigBuilder {
+    fn validate(&self) -> Result<(), &'static str> {
+        match self.data_bits {
+            Some(5..=8) | None => Ok(()),
+            Some(_) => Err("data bits must be between 5 and 8"),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(no_std)]
+pub struct Packet {
+    id: u8,
+    #[builder(each = "byte")]
+    payload: Vec<u8>,
+}
+
+#[derive(Builder)]
+#[builder(no_std, pattern = "owned")]
+pub struct Timer {
+    period: u32,
+    repeat: Option<bool>,
+}
+
+const TIMER: TimerBuilder = Timer::builder();
+
+fn main() {
+    let mut builder = Config::builder();
+    builder.baud_rate(115_200).address(0x42);
+    let config = builder.build().unwrap();
+    assert_eq!(config.baud_rate, 115_200);
+    assert_eq!(config.address, 0x42);
+    assert_eq!(config.retries, None);
+    assert_eq!(config.data_bits, 8);
+
+    let err = Config::builder().build().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields([Some("baud_rate"), Some("address")]),
+    );
+
+    let err = Frame::builder().build().unwrap_err();
+    assert_eq!(err, FrameBuilderError::MissingFields([Some("type")]));
+
+    let err = Config::builder().address(1).build().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigBuilderError::MissingFields([Some("baud_rate"), None]),
+    );
+
+    let err = Config::builder()
+        .baud_rate(9600)
+        .address(1)
+        .data_bits(9)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ConfigBuilderError::Validation("data bits must be between 5 and 8"),
+    );
+
+    let packet = Packet::builder().id(1).byte(0xff).build().unwrap();
+    assert_eq!(packet.payload, [0xff]);
+
+    let timer = TIMER.period(10).build().unwrap();
+    assert_eq!(timer.period, 10);
+    assert_eq!(timer.repeat, None);
+}
@@ -0,0 +1,55 @@
+// With #[builder(setter(try_into))] a field gets a second setter named with a
+// `try_` prefix, which accepts anything that converts into the field type with
+// TryInto and returns the conversion error instead of panicking.
+//
+//     fn try_port<V: TryInto<u16>>(&mut self, port: V) -> Result<&mut Self, V::Error>
+//
+// The setter taking the field type itself is still generated. For an Option
+// field the conversion is into the type inside the Option. Putting it on the
+// struct adds a `try_` setter for every field.
+
+use derive_builder::Builder;
+use std::net::Ipv4Addr;
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(setter(try_into))]
+    port: u16,
+    #[builder(setter(try_into))]
+    workers: Option<u8>,
+    address: Ipv4Addr,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(try_into))]
+pub struct Limits {
+    memory: u32,
+    cpus: Option<u8>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let port: i64 = 8080;
+    let server = Server::builder()
+        .try_port(port)?
+        .try_workers(4u64)?
+        .address(Ipv4Addr::LOCALHOST)
+        .build()?;
+
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, Some(4));
+
+    let mut builder = Server::builder();
+    assert!(builder.try_port(-1).is_err());
+    assert!(builder.try_port(70_000u32).is_err());
+    assert!(builder.try_workers(300).is_err());
+    builder.port(80).address(Ipv4Addr::LOCALHOST);
+    assert_eq!(builder.build()?.port, 80);
+
+    let limits = Limits::builder().try_memory(1024u64)?.try_cpus(2u32)?.build()?;
+    assert_eq!(limits.memory, 1024);
+    assert_eq!(limits.cpus, Some(2));
+
+    assert!(Limits::builder().try_memory(-1i64).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+// #[builder(derive(...))] derives traits for the builder struct itself, so a
+// half-filled builder can be cloned as a template, printed with Debug, or
+// created with Default. #[builder(struct_attr(...))] puts any other attribute
+// on the builder struct as it is.
+//
+// The derived traits need the types of the fields to implement them too. For
+// a typestate builder the state markers derive them as well, except that only
+// a builder with every field unset can be created by Default, written as
+// `<ServerBuilder>::default()`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(
+    derive(Clone, Debug, Default, PartialEq),
+    struct_attr(must_use, doc = "Builds a Command.")
+)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate, derive(Clone, Debug, Default))]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let mut template = CommandBuilder::default();
+    template.executable("cargo".to_owned());
+    assert_eq!(template, *Command::builder().executable("cargo".to_owned()));
+
+    let mut build = template.clone();
+    build.arg("build".to_owned());
+    let mut test = template.clone();
+    test.arg("test".to_owned());
+
+    assert_eq!(build.build().unwrap().args, vec!["build"]);
+    assert_eq!(test.build().unwrap().args, vec!["test"]);
+    assert!(template.build().unwrap().args.is_empty());
+
+    let debug = format!("{:?}", template);
+    assert!(debug.starts_with("CommandBuilder"));
+    assert!(debug.contains("cargo"));
+
+    // Written as a type so that the state parameters take their defaults.
+    let server = <ServerBuilder>::default().host("localhost".to_owned());
+    let first = server.clone().port(8080).build().unwrap();
+    let second = server.port(8081).build().unwrap();
+    assert_eq!(first.port, 8080);
+    assert_eq!(second.port, 8081);
+    assert_eq!(first.host, second.host);
+}
@@ -0,0 +1,33 @@
+// An attribute key that is not recognized is reported at the key. When it is
+// a near miss of a valid key, such as a typo or two swapped letters, the error
+// suggests the closest one; otherwise it lists the keys accepted in that
+// place. This is a compile_fail test.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(eahc = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(patern = "owned")]
+pub struct Server {
+    host: String,
+}
+
+#[derive(Builder)]
+pub struct Client {
+    #[builder(setter(int))]
+    url: String,
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(timeout = "30")]
+    path: String,
+}
+
+fn main() {}
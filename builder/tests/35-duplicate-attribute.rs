@@ -0,0 +1,27 @@
+// Keys may be given in any order, and split across several #[builder(...)]
+// attributes, but each key at most once. A duplicate is reported at its
+// second occurrence. This is a compile_fail test.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default, each = "arg", default = "Vec::new()")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(vis = "pub")]
+#[builder(setter(into), vis = "pub(crate)")]
+pub struct Server {
+    host: String,
+}
+
+#[derive(Builder)]
+pub struct Client {
+    #[builder(setter(into, prefix = "with_", into))]
+    url: String,
+}
+
+fn main() {}
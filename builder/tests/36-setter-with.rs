@@ -0,0 +1,58 @@
+// #[builder(setter(with = "path::to::fn(arg: Type, ...)"))] makes the setter
+// of a field take the given arguments and store what the function returns for
+// them, for fields that need normalizing or parsing when they are set. The
+// function's arguments are written out in the attribute because the derive
+// only sees its path.
+//
+// The builder still stores one Option per field, so an Option field's function
+// returns the value inside the Option, and `build` and `clear_` behave as for
+// any other field.
+
+use derive_builder::Builder;
+use std::time::Duration;
+
+mod normalize {
+    pub fn hostname(host: &str) -> String {
+        host.trim().to_lowercase()
+    }
+}
+
+fn duration(secs: u64, millis: u32) -> Duration {
+    Duration::from_secs(secs) + Duration::from_millis(millis.into())
+}
+
+fn default_port() -> u16 {
+    443
+}
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(setter(with = "normalize::hostname(host: &str)"))]
+    host: String,
+    #[builder(setter(with = "default_port()", prefix = "default_"))]
+    port: u16,
+    #[builder(setter(with = "duration(secs: u64, millis: u32)"))]
+    timeout: Option<Duration>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Client {
+    #[builder(setter(with = "normalize::hostname(host: &str)"))]
+    host: String,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("  Example.COM ")
+        .default_port()
+        .timeout(1, 500)
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "example.com");
+    assert_eq!(server.port, 443);
+    assert_eq!(server.timeout, Some(Duration::from_millis(1500)));
+
+    let client = Client::builder().host("LOCALHOST").build().unwrap();
+    assert_eq!(client.host, "localhost");
+}
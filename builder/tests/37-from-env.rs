@@ -0,0 +1,93 @@
+// #[builder(env_prefix = "APP_")] generates a `from_env` method filling every
+// field that is not set yet from the environment variable named after the
+// field in upper case, after the prefix: `port` is read from `APP_PORT`. The
+// value is parsed with FromStr, and a variable that fails to parse is
+// reported as a ConfigBuilderEnvError with the variable's name. It is a type of
+// its own, so the error type of `build` has no variant that `build` cannot
+// return.
+//
+// A collection's variable holds its items separated by commas, or by the
+// `env_separator` given on the struct or on the field, and the entries of a
+// map are written as `key=value`. A collection counts as unset while it is
+// empty.
+//
+// A field with #[builder(env = false)] is never read from the environment, so
+// its type does not have to implement FromStr.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+#[derive(Builder)]
+#[builder(env_prefix = "APP_")]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(default = "4")]
+    workers: usize,
+    log_level: Option<String>,
+    #[builder(each = "feature")]
+    features: Vec<String>,
+    #[builder(env_separator = ":")]
+    paths: Vec<String>,
+    #[builder(each = "label")]
+    labels: HashMap<String, u32>,
+    #[builder(env = false, default = "Duration::from_secs(30)")]
+    timeout: Duration,
+}
+
+fn main() {
+    env::set_var("APP_HOST", "example.com");
+    env::set_var("APP_PORT", "8080");
+    env::set_var("APP_WORKERS", "16");
+    env::set_var("APP_FEATURES", "tls,http2");
+    env::set_var("APP_PATHS", "/usr/bin:/bin");
+    env::set_var("APP_LABELS", "zone=1,rack=7");
+    env::set_var("APP_TIMEOUT", "5");
+
+    // Fields set before `from_env` keep their values.
+    let config = Config::builder()
+        .host("localhost".to_owned())
+        .from_env()
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.workers, 16);
+    assert_eq!(config.log_level, None);
+    assert_eq!(config.features, ["tls", "http2"]);
+    assert_eq!(config.paths, ["/usr/bin", "/bin"]);
+    assert_eq!(config.labels["zone"], 1);
+    assert_eq!(config.labels["rack"], 7);
+    assert_eq!(config.timeout, Duration::from_secs(30));
+
+    let config = Config::builder()
+        .feature("metrics".to_owned())
+        .from_env()
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(config.host, "example.com");
+    assert_eq!(config.features, ["metrics"]);
+
+    env::set_var("APP_PORT", "eighty");
+    let err = Config::builder().from_env().err().unwrap();
+    assert_eq!(
+        err,
+        ConfigBuilderEnvError {
+            var: "APP_PORT".to_owned(),
+            message: "invalid digit found in string".to_owned(),
+        },
+    );
+    assert_eq!(err.to_string(), "APP_PORT: invalid digit found in string");
+
+    match Config::builder().build() {
+        Ok(_) => panic!("expected missing fields"),
+        Err(ConfigBuilderError::MissingFields(fields)) => assert_eq!(fields, ["host", "port"]),
+        Err(ConfigBuilderError::Validation(_) | ConfigBuilderError::SubBuilder(..)) => {
+            panic!("expected missing fields")
+        }
+    }
+}
@@ -0,0 +1,12 @@
+// The `prefix` of a setter is checked to start an identifier, so that any
+// field name can follow it. This is a compile_fail test.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(setter(prefix = "with-"))]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
@@ -10,4 +10,34 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-typestate.rs");
+    t.compile_fail("tests/11-typestate-missing-field.rs");
+    t.pass("tests/12-error-type.rs");
+    t.pass("tests/13-generics.rs");
+    t.pass("tests/14-default.rs");
+    t.pass("tests/15-pattern.rs");
+    t.pass("tests/16-setter-into.rs");
+    t.pass("tests/17-validate.rs");
+    t.pass("tests/18-enum.rs");
+    t.pass("tests/19-tuple-struct.rs");
+    t.pass("tests/20-collections.rs");
+    t.pass("tests/21-bulk-and-each.rs");
+    t.compile_fail("tests/22-each-name-collision.rs");
+    t.pass("tests/23-skip.rs");
+    t.pass("tests/24-setter-visibility.rs");
+    t.pass("tests/25-strip-option.rs");
+    t.pass("tests/26-to-builder.rs");
+    t.pass("tests/27-sub-builder.rs");
+    t.pass("tests/28-missing-fields.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/29-serde.rs");
+    t.pass("tests/30-merge.rs");
+    t.pass("tests/31-no-std.rs");
+    t.pass("tests/32-try-setter.rs");
+    t.pass("tests/33-builder-derive.rs");
+    t.compile_fail("tests/34-attribute-typo.rs");
+    t.compile_fail("tests/35-duplicate-attribute.rs");
+    t.pass("tests/36-setter-with.rs");
+    t.pass("tests/37-from-env.rs");
+    t.compile_fail("tests/38-invalid-prefix.rs");
 }